@@ -0,0 +1,396 @@
+//! Generates `wasmtime-dl` binding declarations from C headers.
+//!
+//! Headers are parsed with a bundled tree-sitter grammar rather than a real preprocessor, so macros
+//! are not expanded: those that only add attributes or calling conventions (`DECLSPEC`, `SDLCALL`)
+//! must be listed in [`HeaderOptions::ignored_macros`], and typedefs from other headers in
+//! [`HeaderOptions::aliases`].
+//!
+//! Pointer parameters are passed as offsets into guest memory, except for pointers to opaque
+//! structs, which like returned pointers are native objects the guest holds as
+//! [`ParamType::Handle`]s. `char` pointers are strings, passed and returned as [`ParamType::CStr`].
+//! Pointers to pointers, including arrays of pointers, are not supported.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+use thiserror::Error;
+use tree_sitter::{Node, Parser};
+use wasmtime_dl::{Manifest, ManifestFunction, ParamType};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("failed to load the C grammar: {0}")]
+    LanguageError(#[from] tree_sitter::LanguageError),
+    #[error("failed to parse the header")]
+    ParseError,
+}
+
+/// Options for [`parse_header`].
+#[derive(Clone, Debug, Default)]
+pub struct HeaderOptions {
+    /// Macros that are removed before parsing, such as export or calling convention attributes.
+    pub ignored_macros: HashSet<String>,
+    /// Type names and the parameter types they map to. Typedefs in the header itself are resolved
+    /// automatically; these take precedence over them.
+    pub aliases: HashMap<String, ParamType>,
+}
+
+/// The function declarations found in a header.
+#[derive(Clone, Debug, Default)]
+pub struct Declarations {
+    pub functions: Vec<ManifestFunction>,
+    /// Functions whose signatures cannot be represented with [`ParamType`]s.
+    pub unsupported: Vec<Unsupported>,
+}
+
+/// A function that was skipped, and why.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Unsupported {
+    pub name: String,
+    pub reason: String,
+}
+
+/// Finds the function declarations in `source`.
+pub fn parse_header(source: &str, options: &HeaderOptions) -> Result<Declarations, Error> {
+    let source = preprocess(source, &options.ignored_macros);
+    let mut parser = Parser::new();
+    parser.set_language(&tree_sitter_c::LANGUAGE.into())?;
+    let tree = parser.parse(&source, None).ok_or(Error::ParseError)?;
+
+    let mut header = Header {
+        source: source.as_bytes(),
+        aliases: options.aliases.clone(),
+        opaque: HashSet::new(),
+        declarations: Declarations::default(),
+    };
+    header.collect(tree.root_node());
+
+    Ok(header.declarations)
+}
+
+impl Declarations {
+    /// Builds a manifest binding every supported function from `library` into `module`.
+    pub fn to_manifest(&self, library: impl Into<PathBuf>, module: &str) -> Manifest {
+        Manifest {
+            library: library.into(),
+            module: module.to_owned(),
+            memory: None,
+            memory64: false,
+            allocator: None,
+            functions: self.functions.clone(),
+        }
+    }
+
+    /// Emits a `wasmtime_dl::bind` call for every supported function, expecting `linker` and `lib`
+    /// to be in scope and errors to be propagated with `?`.
+    pub fn to_rust(&self, module: &str) -> String {
+        let mut out = String::new();
+        for function in &self.functions {
+            let symbol = function.symbol.as_ref().unwrap_or(&function.name);
+            let params = rust_types(&function.params);
+            let returns = match &function.returns {
+                Some(ty) => format!("Some({})", rust_type(ty)),
+                None => "None".to_owned(),
+            };
+            writeln!(
+                out,
+                "bind(linker, {module:?}, {:?}, lib, b{symbol:?}, &[{params}], {returns})?;",
+                function.name
+            )
+            .unwrap();
+        }
+        out
+    }
+}
+
+/// Headers only map to types without fields, whose `Debug` output is also their Rust syntax.
+fn rust_type(ty: &ParamType) -> String {
+    format!("ParamType::{ty:?}")
+}
+
+fn rust_types(types: &[ParamType]) -> String {
+    types.iter().map(rust_type).collect::<Vec<_>>().join(", ")
+}
+
+struct Header<'a> {
+    source: &'a [u8],
+    aliases: HashMap<String, ParamType>,
+    /// Typedefs of structs that are declared without a body.
+    opaque: HashSet<String>,
+    declarations: Declarations,
+}
+
+impl Header<'_> {
+    fn collect(&mut self, node: Node) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "declaration" => self.declaration(child),
+                "type_definition" => self.type_definition(child),
+                "preproc_if"
+                | "preproc_ifdef"
+                | "preproc_else"
+                | "preproc_elif"
+                | "preproc_elifdef"
+                | "linkage_specification"
+                | "declaration_list" => self.collect(child),
+                _ => {}
+            }
+        }
+    }
+
+    fn declaration(&mut self, node: Node) {
+        let Some(ty) = node.child_by_field_name("type") else {
+            return;
+        };
+        let mut cursor = node.walk();
+        for declarator in node.children_by_field_name("declarator", &mut cursor) {
+            // Peel off the pointers applied to the return type to find the function itself.
+            let mut returns_pointer = false;
+            let mut function = declarator;
+            while function.kind() == "pointer_declarator" {
+                returns_pointer = true;
+                let Some(inner) = function.child_by_field_name("declarator") else {
+                    break;
+                };
+                function = inner;
+            }
+            if function.kind() != "function_declarator" {
+                continue;
+            }
+            let Some(name) = function
+                .child_by_field_name("declarator")
+                .filter(|name| name.kind() == "identifier")
+            else {
+                continue;
+            };
+            let name = self.text(name);
+
+            let signature = if node.has_error() {
+                Err("the declaration could not be parsed".to_owned())
+            } else {
+                self.signature(ty, returns_pointer, function)
+            };
+            match signature {
+                Ok((params, returns)) => self.declarations.functions.push(ManifestFunction {
+                    name,
+                    symbol: None,
+                    memory: None,
+                    params,
+                    returns,
+                    fixed_params: None,
+                }),
+                Err(reason) => self
+                    .declarations
+                    .unsupported
+                    .push(Unsupported { name, reason }),
+            }
+        }
+    }
+
+    fn signature(
+        &self,
+        ty: Node,
+        returns_pointer: bool,
+        function: Node,
+    ) -> Result<(Vec<ParamType>, Option<ParamType>), String> {
+        // Returned strings are copied into guest memory, while other returned pointers don't
+        // point into it, so the guest holds them as handles.
+        let returns = match self.resolve(ty, returns_pointer)? {
+            Some(ParamType::Pointer) if self.is_char(ty) => Some(ParamType::CStr),
+            Some(ParamType::Pointer) => Some(ParamType::Handle),
+            returns => returns,
+        };
+
+        let mut params = Vec::new();
+        if let Some(list) = function.child_by_field_name("parameters") {
+            let mut cursor = list.walk();
+            for param in list.named_children(&mut cursor) {
+                match param.kind() {
+                    "parameter_declaration" => {
+                        let ty = param.child_by_field_name("type").ok_or("missing type")?;
+                        let declarator = param.child_by_field_name("declarator");
+                        let resolved = match pointer_depth(declarator)? {
+                            0 => self.resolve(ty, false)?,
+                            1 if self.is_opaque(ty) => Some(ParamType::Handle),
+                            1 if self.is_char(ty) => Some(ParamType::CStr),
+                            1 => self.resolve(ty, true)?,
+                            _ => return Err("pointers to pointers are not supported".to_owned()),
+                        };
+                        match resolved {
+                            Some(ty) => params.push(ty),
+                            // `(void)` declares that there are no parameters.
+                            None if declarator.is_none() => {}
+                            None => return Err("`void` parameter".to_owned()),
+                        }
+                    }
+                    "variadic_parameter" => {
+                        return Err("variadic functions are not supported".to_owned())
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        Ok((params, returns))
+    }
+
+    fn type_definition(&mut self, node: Node) {
+        let Some(ty) = node.child_by_field_name("type") else {
+            return;
+        };
+        let mut cursor = node.walk();
+        for declarator in node.children_by_field_name("declarator", &mut cursor) {
+            let (name, pointer) = match declarator.kind() {
+                "type_identifier" => (declarator, false),
+                "pointer_declarator" => match declarator.child_by_field_name("declarator") {
+                    Some(name) if name.kind() == "type_identifier" => (name, true),
+                    _ => continue,
+                },
+                _ => continue,
+            };
+            let name = self.text(name);
+            if pointer && self.is_opaque(ty) {
+                self.aliases.entry(name).or_insert(ParamType::Handle);
+            } else if let Ok(Some(resolved)) = self.resolve(ty, pointer) {
+                self.aliases.entry(name).or_insert(resolved);
+            } else if is_opaque_struct(ty) {
+                self.opaque.insert(name);
+            }
+        }
+    }
+
+    /// Maps a C type to the parameter type it is passed as, or `None` for `void`.
+    fn resolve(&self, ty: Node, pointer: bool) -> Result<Option<ParamType>, String> {
+        if pointer {
+            return Ok(Some(ParamType::Pointer));
+        }
+        let name = self
+            .text(ty)
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        let resolved = match name.as_str() {
+            "void" => return Ok(None),
+            "int" | "signed" | "signed int" | "int32_t" => ParamType::I32,
+            "unsigned" | "unsigned int" | "uint32_t" => ParamType::U32,
+            "long long" | "long long int" | "signed long long" | "int64_t" => ParamType::I64,
+            "unsigned long long" | "unsigned long long int" | "uint64_t" => ParamType::U64,
+            "signed char" | "int8_t" => ParamType::I8,
+            "unsigned char" | "uint8_t" => ParamType::U8,
+            "short" | "short int" | "signed short" | "int16_t" => ParamType::I16,
+            "unsigned short" | "unsigned short int" | "uint16_t" => ParamType::U16,
+            "bool" | "_Bool" => ParamType::Bool,
+            "size_t" | "uintptr_t" => ParamType::Usize,
+            "ssize_t" | "ptrdiff_t" | "intptr_t" => ParamType::Isize,
+            "float" => ParamType::F32,
+            "double" => ParamType::F64,
+            _ => match self.aliases.get(&name) {
+                Some(ty) => ty.clone(),
+                None => return Err(format!("unsupported type `{name}`")),
+            },
+        };
+        Ok(Some(resolved))
+    }
+
+    /// Whether `ty` names a struct that is declared without a body, whose pointers are handles.
+    fn is_opaque(&self, ty: Node) -> bool {
+        match ty.kind() {
+            "type_identifier" => self.opaque.contains(&self.text(ty)),
+            _ => is_opaque_struct(ty),
+        }
+    }
+
+    /// Whether `ty` is `char`, whose pointers are strings.
+    fn is_char(&self, ty: Node) -> bool {
+        ty.kind() == "primitive_type" && self.text(ty) == "char"
+    }
+
+    fn text(&self, node: Node) -> String {
+        String::from_utf8_lossy(&self.source[node.byte_range()]).into_owned()
+    }
+}
+
+fn is_opaque_struct(ty: Node) -> bool {
+    matches!(ty.kind(), "struct_specifier" | "union_specifier")
+        && ty.child_by_field_name("body").is_none()
+}
+
+/// How many levels of pointer a parameter declarator adds to its type. Arrays decay to pointers,
+/// and function pointers are rejected.
+fn pointer_depth(mut declarator: Option<Node>) -> Result<usize, String> {
+    let mut depth = 0;
+    while let Some(node) = declarator {
+        match node.kind() {
+            "pointer_declarator"
+            | "abstract_pointer_declarator"
+            | "array_declarator"
+            | "abstract_array_declarator" => depth += 1,
+            "function_declarator" | "abstract_function_declarator" => {
+                return Err("function pointer parameters are not supported".to_owned())
+            }
+            _ => {}
+        }
+        declarator = node.child_by_field_name("declarator");
+    }
+    Ok(depth)
+}
+
+/// Blanks out the C++-only branches of `#ifdef __cplusplus` blocks, which usually hold an
+/// unbalanced `extern "C" {`, and every use of the ignored macros. Line structure is preserved.
+fn preprocess(source: &str, ignored_macros: &HashSet<String>) -> String {
+    let mut out = String::with_capacity(source.len());
+    // Nesting depth inside a `__cplusplus` conditional, and whether its C++ branch is active.
+    let mut depth = 0;
+    let mut skipping = false;
+    for line in source.split_inclusive('\n') {
+        let directive = line.trim_start();
+        if depth == 0 {
+            if is_cplusplus_check(directive) {
+                depth = 1;
+                skipping = true;
+            }
+        } else if directive.starts_with("#if") {
+            depth += 1;
+        } else if directive.starts_with("#endif") {
+            depth -= 1;
+        } else if depth == 1 && (directive.starts_with("#else") || directive.starts_with("#elif")) {
+            skipping = false;
+        } else if skipping {
+            out.push_str(if line.ends_with('\n') { "\n" } else { "" });
+            continue;
+        }
+        blank_macros(line, ignored_macros, &mut out);
+    }
+    out
+}
+
+fn is_cplusplus_check(directive: &str) -> bool {
+    let directive = directive.split_whitespace().collect::<Vec<_>>().join(" ");
+    [
+        "#ifdef __cplusplus",
+        "#if defined(__cplusplus)",
+        "#if defined __cplusplus",
+    ]
+    .contains(&directive.as_str())
+}
+
+fn blank_macros(line: &str, ignored_macros: &HashSet<String>, out: &mut String) {
+    let mut rest = line;
+    while let Some(start) = rest.find(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        let (before, ident) = rest.split_at(start);
+        let len = ident
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(ident.len());
+        let (ident, after) = ident.split_at(len);
+        out.push_str(before);
+        if ignored_macros.contains(ident) {
+            out.extend(std::iter::repeat_n(' ', ident.len()));
+        } else {
+            out.push_str(ident);
+        }
+        rest = after;
+    }
+    out.push_str(rest);
+}
@@ -0,0 +1,90 @@
+use std::process::ExitCode;
+use std::{env, fs};
+
+use serde::de::value::{Error as ValueError, StrDeserializer};
+use serde::Deserialize;
+use wasmtime_dl::ParamType;
+use wasmtime_dl_bindgen::{parse_header, HeaderOptions};
+
+const USAGE: &str = "\
+usage: wasmtime-dl-bindgen <header> --module <name> [--library <path>] [--format toml|json|rust]
+                           [--ignore <macro>]... [--alias <type>=<param type>]...";
+
+enum Format {
+    Toml,
+    Json,
+    Rust,
+}
+
+fn main() -> ExitCode {
+    match run(env::args().skip(1)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("error: {message}\n{USAGE}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let mut header = None;
+    let mut module = None;
+    let mut library = None;
+    let mut format = Format::Toml;
+    let mut options = HeaderOptions::default();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("missing value for {arg}"));
+        match arg.as_str() {
+            "--module" => module = Some(value()?),
+            "--library" => library = Some(value()?),
+            "--format" => {
+                format = match value()?.as_str() {
+                    "toml" => Format::Toml,
+                    "json" => Format::Json,
+                    "rust" => Format::Rust,
+                    other => return Err(format!("unknown format {other}")),
+                }
+            }
+            "--ignore" => {
+                options.ignored_macros.insert(value()?);
+            }
+            "--alias" => {
+                let alias = value()?;
+                let (name, ty) = alias
+                    .split_once('=')
+                    .ok_or(format!("expected <type>=<param type>, got {alias}"))?;
+                let ty = ParamType::deserialize(StrDeserializer::<ValueError>::new(ty))
+                    .map_err(|err| format!("invalid parameter type in {alias}: {err}"))?;
+                options.aliases.insert(name.to_owned(), ty);
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option {arg}")),
+            _ if header.is_none() => header = Some(arg),
+            _ => return Err(format!("unexpected argument {arg}")),
+        }
+    }
+
+    let header = header.ok_or("missing header")?;
+    let module = module.ok_or("missing --module")?;
+    let source = fs::read_to_string(&header).map_err(|err| format!("{header}: {err}"))?;
+    let declarations = parse_header(&source, &options).map_err(|err| err.to_string())?;
+
+    for skipped in &declarations.unsupported {
+        eprintln!("warning: skipping {}: {}", skipped.name, skipped.reason);
+    }
+
+    let output = match format {
+        Format::Rust => declarations.to_rust(&module),
+        Format::Toml | Format::Json => {
+            let library = library.ok_or("missing --library")?;
+            let manifest = declarations.to_manifest(library, &module);
+            match format {
+                Format::Json => serde_json::to_string_pretty(&manifest).unwrap() + "\n",
+                _ => toml::to_string(&manifest).map_err(|err| err.to_string())?,
+            }
+        }
+    };
+    print!("{output}");
+
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use wasmtime_dl::{Manifest, ParamType};
+use wasmtime_dl_bindgen::{parse_header, Declarations, HeaderOptions, Unsupported};
+
+const HEADER: &str = include_str!("headers/window.h");
+
+fn parse() -> Declarations {
+    let options = HeaderOptions {
+        ignored_macros: ["DECLSPEC", "SDLCALL"].map(String::from).into(),
+        ..HeaderOptions::default()
+    };
+    parse_header(HEADER, &options).unwrap()
+}
+
+#[test]
+fn maps_c_types_to_param_types() {
+    let declarations = parse();
+    let functions: Vec<_> = declarations
+        .functions
+        .iter()
+        .map(|f| (f.name.as_str(), f.params.as_slice(), f.returns.clone()))
+        .collect();
+
+    assert_eq!(
+        functions,
+        [
+            (
+                "CreateWindow",
+                &[
+                    ParamType::CStr,
+                    ParamType::I32,
+                    ParamType::I32,
+                    ParamType::U32
+                ][..],
+                Some(ParamType::Handle)
+            ),
+            ("DestroyWindow", &[ParamType::Handle][..], None),
+            ("GetTicks", &[][..], Some(ParamType::U32)),
+            ("GetPerformanceCounter", &[][..], Some(ParamType::I64)),
+            (
+                "Lerp",
+                &[ParamType::F64, ParamType::F64, ParamType::F32][..],
+                Some(ParamType::F64)
+            ),
+            (
+                "SetColor",
+                &[
+                    ParamType::Handle,
+                    ParamType::U8,
+                    ParamType::I16,
+                    ParamType::Usize
+                ][..],
+                Some(ParamType::Bool)
+            ),
+            (
+                "GetUserData",
+                &[ParamType::Handle][..],
+                Some(ParamType::Handle)
+            ),
+            ("GetTitle", &[ParamType::Handle][..], Some(ParamType::CStr)),
+            (
+                "ReadPixels",
+                &[ParamType::Handle, ParamType::Pointer, ParamType::Usize][..],
+                None
+            ),
+        ]
+    );
+}
+
+#[test]
+fn passes_char_pointers_as_strings() {
+    let declarations = parse_header("int Puts(const char *s);", &HeaderOptions::default()).unwrap();
+
+    assert_eq!(declarations.functions[0].params, [ParamType::CStr]);
+}
+
+#[test]
+fn reports_functions_it_cannot_represent() {
+    let declarations = parse();
+    let unsupported = |name: &str, reason: &str| Unsupported {
+        name: name.to_owned(),
+        reason: reason.to_owned(),
+    };
+
+    assert_eq!(
+        declarations.unsupported,
+        [
+            unsupported("SetTitles", "pointers to pointers are not supported"),
+            unsupported("Log", "variadic functions are not supported"),
+            unsupported("SetFilter", "function pointer parameters are not supported"),
+            unsupported("GetLong", "unsupported type `long`"),
+            unsupported("MovePoint", "unsupported type `Point`"),
+        ]
+    );
+}
+
+#[test]
+fn aliases_override_typedefs() {
+    let options = HeaderOptions {
+        ignored_macros: ["DECLSPEC", "SDLCALL"].map(String::from).into(),
+        aliases: [("Uint32".to_owned(), ParamType::I64)].into(),
+    };
+    let declarations = parse_header(HEADER, &options).unwrap();
+
+    let get_ticks = declarations
+        .functions
+        .iter()
+        .find(|f| f.name == "GetTicks")
+        .unwrap();
+    assert_eq!(get_ticks.returns, Some(ParamType::I64));
+}
+
+#[test]
+fn emits_manifests_and_rust() {
+    let declarations = parse();
+
+    let toml = toml::to_string(&declarations.to_manifest("libwindow.so", "window")).unwrap();
+    let manifest = Manifest::from_toml(&toml).unwrap();
+    assert_eq!(manifest.module, "window");
+    assert_eq!(manifest.functions.len(), declarations.functions.len());
+
+    let rust = declarations.to_rust("window");
+    assert!(rust.contains(
+        r#"bind(linker, "window", "GetTicks", lib, b"GetTicks", &[], Some(ParamType::U32))?;"#
+    ));
+}
@@ -0,0 +1,39 @@
+#ifndef WINDOW_H
+#define WINDOW_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef uint32_t Uint32;
+typedef struct Window Window;
+typedef struct Point {
+    float x, y;
+} Point;
+
+/* Creates a window. */
+extern DECLSPEC Window * SDLCALL CreateWindow(const char *title, int w, int h, Uint32 flags);
+extern DECLSPEC void SDLCALL DestroyWindow(Window *window);
+extern DECLSPEC Uint32 SDLCALL GetTicks(void);
+extern DECLSPEC int64_t SDLCALL GetPerformanceCounter(void);
+extern DECLSPEC double SDLCALL Lerp(double a, double b, float t);
+extern DECLSPEC void SDLCALL SetTitles(Window *windows[], int count);
+extern DECLSPEC bool SDLCALL SetColor(Window *window, uint8_t r, int16_t depth, size_t len);
+extern DECLSPEC void * SDLCALL GetUserData(struct Window *window);
+extern DECLSPEC const char * SDLCALL GetTitle(Window *window);
+extern DECLSPEC void SDLCALL ReadPixels(Window *window, uint8_t *pixels, size_t len);
+
+extern DECLSPEC void SDLCALL Log(const char *fmt, ...);
+extern DECLSPEC void SDLCALL SetFilter(int (*filter)(void *userdata), void *userdata);
+extern DECLSPEC long SDLCALL GetLong(void);
+extern DECLSPEC void SDLCALL MovePoint(Point point);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
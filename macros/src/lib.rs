@@ -0,0 +1,262 @@
+//! The `#[import]` attribute, re-exported as `wasmtime_dl::import`.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, Expr, ExprLit, FnArg, ForeignItem, ForeignItemFn, ItemForeignMod, Lit,
+    LitBool, LitByteStr, LitStr, Meta, ReturnType, Type,
+};
+
+/// Generates typed bindings for the functions declared in an `extern "C"` block.
+///
+/// ```ignore
+/// #[wasmtime_dl::import(lib = "SDL2", module = "sdl")]
+/// extern "C" {
+///     fn SDL_GetTicks() -> u32;
+///     fn SDL_SetWindowTitle(window: *mut SDL_Window, title: *const c_char);
+/// }
+/// ```
+///
+/// The block is replaced by two functions in the same scope: `add_to_linker`, which loads the
+/// library named by `lib` (as a platform library name, so `SDL2` becomes `libSDL2.so`, `SDL2.dll`
+/// or `libSDL2.dylib`), and `add_to_linker_with_library`, which takes an already loaded library.
+/// Both define every function in the block as an import of `module`.
+///
+/// Parameters and returns may be Rust's integer and float types, `bool` or raw pointers, with the
+/// same conversions as the corresponding `ParamType`s. Like `ParamType::Pointer`, pointers are
+/// guest offsets translated to and from the memory the caller exports under the name given by the
+/// optional `memory` argument (`memory` by default); memories the module only imports are bound
+/// with `wasmtime_dl::bind_with_options` instead. With `memory64 = true`, offsets, `usize` and
+/// `isize` are passed as `i64`s, as with `BindOptions::memory64`. A `#[link_name]` attribute sets
+/// the native symbol when it differs from the import name. Functions may take at most 16
+/// parameters.
+#[proc_macro_attribute]
+pub fn import(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut args = Args::default();
+    let parser = syn::meta::parser(|meta| {
+        let slot = if meta.path.is_ident("lib") {
+            &mut args.lib
+        } else if meta.path.is_ident("module") {
+            &mut args.module
+        } else if meta.path.is_ident("memory") {
+            &mut args.memory
+        } else if meta.path.is_ident("memory64") {
+            args.memory64 = meta.value()?.parse::<LitBool>()?.value;
+            return Ok(());
+        } else {
+            return Err(meta.error("expected `lib`, `module`, `memory` or `memory64`"));
+        };
+        *slot = Some(meta.value()?.parse()?);
+        Ok(())
+    });
+    parse_macro_input!(attr with parser);
+    let block = parse_macro_input!(item as ItemForeignMod);
+
+    expand(args, block)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct Args {
+    lib: Option<LitStr>,
+    module: Option<LitStr>,
+    memory: Option<LitStr>,
+    memory64: bool,
+}
+
+fn expand(args: Args, block: ItemForeignMod) -> syn::Result<TokenStream2> {
+    let missing = |name| syn::Error::new(Span::call_site(), format!("missing `{name}` argument"));
+    let lib = args.lib.ok_or_else(|| missing("lib"))?;
+    let module = args.module.ok_or_else(|| missing("module"))?;
+    let memory = match args.memory {
+        Some(name) => quote!(__private::GuestMemory::Export(::std::string::String::from(#name))),
+        None => quote!(__private::GuestMemory::default()),
+    };
+    if block.abi.name.as_ref().map(LitStr::value).as_deref() != Some("C") {
+        return Err(syn::Error::new_spanned(
+            &block.abi,
+            "only `extern \"C\"` blocks can be imported",
+        ));
+    }
+
+    let bindings = block
+        .items
+        .iter()
+        .map(|item| match item {
+            ForeignItem::Fn(func) => binding(func, &module, args.memory64),
+            _ => Err(syn::Error::new_spanned(
+                item,
+                "only functions can be imported",
+            )),
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    Ok(quote! {
+        /// Loads the library and binds every function declared in the `extern` block.
+        ///
+        /// # Safety
+        ///
+        /// Loading the library runs its initialization code, and every function must have the
+        /// declared signature.
+        #[allow(dead_code)]
+        pub unsafe fn add_to_linker<__T: 'static>(
+            linker: &mut ::wasmtime_dl::__private::wasmtime::Linker<__T>,
+        ) -> ::std::result::Result<(), ::wasmtime_dl::Error> {
+            use ::wasmtime_dl::__private;
+
+            let path = __private::libloading::library_filename(#lib);
+            let lib = unsafe { __private::libloading::Library::new(&path) }.map_err(|source| {
+                __private::Error::LibraryLoadError {
+                    path: path.into(),
+                    source,
+                }
+            })?;
+            unsafe { add_to_linker_with_library(linker, &::std::sync::Arc::new(lib)) }
+        }
+
+        /// Binds every function declared in the `extern` block from an already loaded library.
+        ///
+        /// # Safety
+        ///
+        /// Every function must have the declared signature.
+        #[allow(dead_code)]
+        pub unsafe fn add_to_linker_with_library<__T: 'static>(
+            linker: &mut ::wasmtime_dl::__private::wasmtime::Linker<__T>,
+            lib: &::std::sync::Arc<::wasmtime_dl::__private::libloading::Library>,
+        ) -> ::std::result::Result<(), ::wasmtime_dl::Error> {
+            use ::wasmtime_dl::__private;
+
+            let memory = #memory;
+            #(#bindings)*
+            Ok(())
+        }
+    })
+}
+
+fn binding(func: &ForeignItemFn, module: &LitStr, memory64: bool) -> syn::Result<TokenStream2> {
+    let sig = &func.sig;
+    if let Some(variadic) = &sig.variadic {
+        return Err(syn::Error::new_spanned(
+            variadic,
+            "variadic functions are not supported",
+        ));
+    }
+    // The most parameters `Linker::func_wrap` accepts.
+    if sig.inputs.len() > 16 {
+        return Err(syn::Error::new_spanned(
+            &sig.inputs,
+            "at most 16 parameters are supported; bind longer signatures with `wasmtime_dl::bind`",
+        ));
+    }
+    let name = sig.ident.to_string();
+    let symbol = link_name(func)?.unwrap_or_else(|| name.clone());
+    let symbol_bytes = LitByteStr::new(symbol.as_bytes(), Span::call_site());
+
+    let mut native_tys = Vec::new();
+    let mut wasm_args = Vec::new();
+    let mut wasm_tys = Vec::new();
+    let mut conversions = Vec::new();
+    let mut uses_memory = false;
+    let offset_ty = if memory64 { quote!(i64) } else { quote!(i32) };
+    for (index, input) in sig.inputs.iter().enumerate() {
+        let FnArg::Typed(input) = input else {
+            return Err(syn::Error::new_spanned(input, "unexpected receiver"));
+        };
+        let ty = &*input.ty;
+        let arg = format_ident!("arg{index}");
+        if let Type::Ptr(_) = ty {
+            uses_memory = true;
+            wasm_tys.push(offset_ty.clone());
+            conversions.push(quote!(__private::guest_ptr(&mut caller, &memory, #arg)? as #ty));
+        } else if memory64 && is_size(ty) {
+            wasm_tys.push(quote!(i64));
+            conversions.push(quote!(#arg as #ty));
+        } else {
+            wasm_tys.push(quote!(<#ty as __private::WasmAbi>::Wasm));
+            conversions.push(quote!(<#ty as __private::WasmAbi>::from_wasm(#arg)));
+        }
+        native_tys.push(ty);
+        wasm_args.push(arg);
+    }
+
+    let output = &sig.output;
+    let (ret_ty, ret) = match output {
+        ReturnType::Type(_, ty) if matches!(&**ty, Type::Tuple(tuple) if tuple.elems.is_empty()) => {
+            (quote!(()), quote!(Ok(ret)))
+        }
+        ReturnType::Default => (quote!(()), quote!(Ok(ret))),
+        ReturnType::Type(_, ty) if matches!(&**ty, Type::Ptr(_)) => {
+            uses_memory = true;
+            let ret = quote!(__private::guest_offset(&mut caller, &memory, ret as usize));
+            (offset_ty, ret)
+        }
+        ReturnType::Type(_, ty) if memory64 && is_size(ty) => (quote!(i64), quote!(Ok(ret as i64))),
+        ReturnType::Type(_, ty) => (
+            quote!(<#ty as __private::WasmAbi>::Wasm),
+            quote!(Ok(<#ty as __private::WasmAbi>::into_wasm(ret))),
+        ),
+    };
+    let caller = if uses_memory {
+        quote!(mut caller)
+    } else {
+        quote!(_caller)
+    };
+
+    Ok(quote! {
+        {
+            let native: unsafe extern "C" fn(#(#native_tys),*) #output = unsafe {
+                *lib.get(#symbol_bytes).map_err(|source| __private::Error::SymbolNotFound {
+                    symbol: ::std::string::String::from(#symbol),
+                    source,
+                })?
+            };
+            let lib = ::std::sync::Arc::clone(lib);
+            let memory = memory.clone();
+            linker
+                .func_wrap(
+                    #module,
+                    #name,
+                    move |#caller: __private::wasmtime::Caller<'_, __T>, #(#wasm_args: #wasm_tys),*|
+                        -> __private::wasmtime::Result<#ret_ty> {
+                        let _ = (&lib, &memory);
+                        let ret = unsafe { native(#(#conversions),*) };
+                        #ret
+                    },
+                )
+                .map_err(|_| __private::Error::DuplicateDefinition {
+                    module: ::std::string::String::from(#module),
+                    name: ::std::string::String::from(#name),
+                })?;
+        }
+    })
+}
+
+/// Whether `ty` is `usize` or `isize`, which follow the width of guest offsets.
+fn is_size(ty: &Type) -> bool {
+    matches!(ty, Type::Path(path) if path.path.is_ident("usize") || path.path.is_ident("isize"))
+}
+
+/// The symbol named by a `#[link_name = "..."]` attribute, if there is one.
+fn link_name(func: &ForeignItemFn) -> syn::Result<Option<String>> {
+    for attr in &func.attrs {
+        if !attr.path().is_ident("link_name") {
+            continue;
+        }
+        if let Meta::NameValue(meta) = &attr.meta {
+            if let Expr::Lit(ExprLit {
+                lit: Lit::Str(name),
+                ..
+            }) = &meta.value
+            {
+                return Ok(Some(name.value()));
+            }
+        }
+        return Err(syn::Error::new_spanned(
+            attr,
+            "expected `#[link_name = \"...\"]`",
+        ));
+    }
+    Ok(None)
+}
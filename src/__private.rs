@@ -0,0 +1,117 @@
+//! Support code for the `#[import]` macro. Not part of the public API.
+
+pub use libloading;
+pub use wasmtime;
+
+pub use crate::{Error, GuestMemory};
+
+use wasmtime::Caller;
+
+/// A native scalar type and the WASM value type it is passed as.
+pub trait WasmAbi {
+    type Wasm;
+
+    fn from_wasm(value: Self::Wasm) -> Self;
+    fn into_wasm(self) -> Self::Wasm;
+}
+
+macro_rules! wasm_abi {
+    ($($native:ty => $wasm:ty),* $(,)?) => {
+        $(
+            impl WasmAbi for $native {
+                type Wasm = $wasm;
+
+                fn from_wasm(value: $wasm) -> Self {
+                    value as $native
+                }
+
+                fn into_wasm(self) -> $wasm {
+                    self as $wasm
+                }
+            }
+        )*
+    };
+}
+
+wasm_abi! {
+    i32 => i32,
+    u32 => i32,
+    i64 => i64,
+    u64 => i64,
+    f32 => f32,
+    f64 => f64,
+    u8 => i32,
+    i8 => i32,
+    u16 => i32,
+    i16 => i32,
+    isize => i32,
+}
+
+impl WasmAbi for usize {
+    type Wasm = i32;
+
+    fn from_wasm(value: i32) -> Self {
+        value as u32 as usize
+    }
+
+    fn into_wasm(self) -> i32 {
+        self as i32
+    }
+}
+
+impl WasmAbi for bool {
+    type Wasm = i32;
+
+    fn from_wasm(value: i32) -> Self {
+        value != 0
+    }
+
+    fn into_wasm(self) -> i32 {
+        self.into()
+    }
+}
+
+/// A guest offset, which is an `i32` in a 32-bit memory and an `i64` in a 64-bit one.
+pub trait GuestOffset {
+    fn from_offset(offset: u64) -> Self;
+    fn into_offset(self) -> u64;
+}
+
+impl GuestOffset for i32 {
+    fn from_offset(offset: u64) -> Self {
+        offset as i32
+    }
+
+    fn into_offset(self) -> u64 {
+        self as u32 as u64
+    }
+}
+
+impl GuestOffset for i64 {
+    fn from_offset(offset: u64) -> Self {
+        offset as i64
+    }
+
+    fn into_offset(self) -> u64 {
+        self as u64
+    }
+}
+
+/// Translates a guest pointer parameter into a host address.
+pub fn guest_ptr<T>(
+    caller: &mut Caller<'_, T>,
+    memory: &GuestMemory,
+    offset: impl GuestOffset,
+) -> wasmtime::Result<usize> {
+    crate::bind::mem_ptr(caller, memory, offset.into_offset(), 0)
+}
+
+/// Translates a returned host pointer back into a guest offset, trapping if it points outside of
+/// the guest's memory.
+pub fn guest_offset<T, O: GuestOffset>(
+    caller: &mut Caller<'_, T>,
+    memory: &GuestMemory,
+    ptr: usize,
+) -> wasmtime::Result<O> {
+    crate::bind::guest_offset(caller, memory, ptr, 0).map(O::from_offset)
+}
@@ -0,0 +1,312 @@
+use std::cell::RefCell;
+use std::collections::hash_map::Entry;
+use std::ffi::c_void;
+use std::sync::Arc;
+
+use libffi::low::{self, ffi_cif, ffi_closure};
+use libffi::middle::{Cif, CodePtr, Type};
+use wasmtime::{Caller, Extern, Func, Ref, Trap, Val};
+
+use crate::bind::{
+    ffi_type, guest_memory, handle_table_full, LinearMemory, MemoryKey, NativeValue,
+};
+use crate::store::{self, StoreKey, StrayError};
+use crate::{BindOptions, CallbackType, GuestTable, HandleTable, ParamType};
+
+/// A native function pointer that calls a WASM function, for [`ParamType::Callback`].
+///
+/// It enters the guest through the innermost native call running on the calling thread, which
+/// must be from the same store, and otherwise returns zero.
+pub(crate) struct Callback {
+    closure: *mut ffi_closure,
+    code: CodePtr,
+    // Referenced by the closure, so boxed to keep their addresses stable.
+    cif: Box<Cif>,
+    data: Box<CallbackData>,
+}
+
+// The closure only reads its data, and only enters the store on a thread that is running a native
+// call from it.
+unsafe impl Send for Callback {}
+unsafe impl Sync for Callback {}
+
+struct CallbackData {
+    /// The store that `func` belongs to, as identified by [`store::key`].
+    store: StoreKey,
+    func: Func,
+    ty: CallbackType,
+    /// The memory that pointer parameters point into, for callbacks that take any. It is found
+    /// when the callback is created, since a retained callback may later be called during a
+    /// native call from another instance.
+    memory: Option<LinearMemory>,
+    memory64: bool,
+    stray_error: Arc<StrayError>,
+}
+
+thread_local! {
+    /// The native calls running on this thread, innermost last.
+    static ACTIVE_CALLS: RefCell<Vec<ActiveCall>> = const { RefCell::new(Vec::new()) };
+}
+
+struct ActiveCall {
+    store: StoreKey,
+    /// The `Caller` of the call, erased because callbacks aren't generic.
+    caller: *mut c_void,
+    /// The first error from a callback made during the call.
+    error: Option<wasmtime::Error>,
+}
+
+/// Lets callbacks enter the store of a native call while it runs, until it is dropped.
+pub(crate) struct CallScope {
+    index: usize,
+}
+
+impl CallScope {
+    /// Starts a native call from the store of `caller`, which must stay borrowed until the scope
+    /// is dropped.
+    pub(crate) fn enter<T: 'static>(caller: &mut Caller<'_, T>) -> CallScope {
+        let call = ActiveCall {
+            store: store::key(&*caller),
+            caller: caller as *mut Caller<'_, T> as *mut c_void,
+            error: None,
+        };
+        ACTIVE_CALLS.with_borrow_mut(|calls| {
+            calls.push(call);
+            CallScope {
+                index: calls.len() - 1,
+            }
+        })
+    }
+
+    /// Ends the call, returning the first error from a callback made during it.
+    pub(crate) fn exit(self) -> Option<wasmtime::Error> {
+        ACTIVE_CALLS.with_borrow_mut(|calls| calls[self.index].error.take())
+    }
+}
+
+impl Drop for CallScope {
+    fn drop(&mut self) {
+        ACTIVE_CALLS.with_borrow_mut(|calls| calls.truncate(self.index));
+    }
+}
+
+/// Returns a function pointer that calls the function at `index` in the guest's table.
+///
+/// Unless the callback is retained, it is added to `callbacks`, which must outlive the native call.
+pub(crate) fn callback<T: 'static>(
+    caller: &mut Caller<'_, T>,
+    options: &BindOptions,
+    ty: &CallbackType,
+    index: u32,
+    callbacks: &mut Vec<Callback>,
+) -> wasmtime::Result<usize> {
+    let table = match &options.table {
+        GuestTable::Export(name) => match caller.get_export(name) {
+            Some(Extern::Table(table)) => table,
+            _ => {
+                return Err(wasmtime::Error::msg(format!(
+                    "caller does not export a table named `{name}`"
+                )))
+            }
+        },
+        GuestTable::Host(table) => *table,
+    };
+    let func = match table.get(&mut *caller, index) {
+        Some(Ref::Func(Some(func))) => func,
+        Some(Ref::Func(None)) => return Err(Trap::IndirectCallToNull.into()),
+        Some(_) => return Err(Trap::BadSignature.into()),
+        None => return Err(Trap::TableOutOfBounds.into()),
+    };
+    if !func.matches_ty(&*caller, &ty.func_type(caller.engine(), options.memory64)) {
+        return Err(Trap::BadSignature.into());
+    }
+
+    let memory = if ty.params.contains(&ParamType::Pointer) {
+        Some(guest_memory(caller, &options.memory)?)
+    } else {
+        None
+    };
+    let state = store::state(&mut *caller);
+    let data = CallbackData {
+        store: store::key(&*caller),
+        func,
+        ty: ty.clone(),
+        memory,
+        memory64: options.memory64,
+        stray_error: Arc::clone(&state.stray_error),
+    };
+    if !ty.retained {
+        let callback = Callback::new::<T>(data)?;
+        let code = callback.code();
+        callbacks.push(callback);
+        return Ok(code);
+    }
+    let key = RetainedKey {
+        func: unsafe { func.to_raw(&mut *caller) } as usize,
+        ty: ty.clone(),
+        memory: data.memory.as_ref().map(LinearMemory::key),
+        memory64: options.memory64,
+    };
+    let mut retained = state.callbacks.lock().unwrap();
+    Ok(match retained.entry(key) {
+        Entry::Occupied(entry) => entry.get().code(),
+        Entry::Vacant(entry) => entry.insert(Callback::new::<T>(data)?).code(),
+    })
+}
+
+/// Identifies a retained callback: the same function, called with the same signature and
+/// memory, is passed to native code as the same function pointer.
+#[derive(PartialEq, Eq, Hash)]
+pub(crate) struct RetainedKey {
+    /// The raw function reference of the WASM function.
+    func: usize,
+    ty: CallbackType,
+    memory: Option<MemoryKey>,
+    memory64: bool,
+}
+
+impl Callback {
+    fn new<T: 'static>(data: CallbackData) -> wasmtime::Result<Callback> {
+        let cif = Box::new(Cif::new(
+            data.ty.params.iter().map(ffi_type),
+            data.ty.returns.as_deref().map_or_else(Type::void, ffi_type),
+        ));
+        let (closure, code) = low::closure_alloc();
+        if closure.is_null() {
+            return Err(wasmtime::Error::msg("failed to allocate a callback"));
+        }
+        // Constructed first so that the closure is freed if preparing it fails.
+        let callback = Callback {
+            closure,
+            code,
+            cif,
+            data: Box::new(data),
+        };
+        unsafe {
+            low::prep_closure(
+                closure,
+                callback.cif.as_raw_ptr(),
+                trampoline::<T>,
+                &*callback.data,
+                code,
+            )
+        }
+        .map_err(|_| wasmtime::Error::msg("failed to prepare a callback"))?;
+        Ok(callback)
+    }
+
+    fn code(&self) -> usize {
+        self.code.as_ptr() as usize
+    }
+}
+
+impl Drop for Callback {
+    fn drop(&mut self) {
+        unsafe { low::closure_free(self.closure) }
+    }
+}
+
+unsafe extern "C" fn trampoline<T: 'static>(
+    _cif: &ffi_cif,
+    result: &mut NativeValue,
+    args: *const *const c_void,
+    data: &CallbackData,
+) {
+    if data.ty.returns.is_some() {
+        *result = NativeValue { i64: 0 };
+    }
+    let call = ACTIVE_CALLS.with_borrow(|calls| {
+        let call = calls.last().filter(|call| call.store == data.store)?;
+        Some((calls.len() - 1, call.caller, call.error.is_some()))
+    });
+    let (index, caller) = match call {
+        Some((index, caller, false)) => (index, caller),
+        // Native code can't be unwound through, so after a trap the callback only returns zero.
+        Some((_, _, true)) => return,
+        None => {
+            data.stray_error.report(wasmtime::Error::msg(
+                "callback was called while no native call from its store was running on the \
+                 thread",
+            ));
+            return;
+        }
+    };
+    let caller = &mut *(caller as *mut Caller<'_, T>);
+    if let Err(err) = data.call(caller, result, args) {
+        ACTIVE_CALLS.with_borrow_mut(|calls| calls[index].error = Some(err));
+    }
+}
+
+impl CallbackData {
+    unsafe fn call<T: 'static>(
+        &self,
+        caller: &mut Caller<'_, T>,
+        result: &mut NativeValue,
+        args: *const *const c_void,
+    ) -> wasmtime::Result<()> {
+        let mut params = Vec::with_capacity(self.ty.params.len());
+        for (i, ty) in self.ty.params.iter().enumerate() {
+            let arg = *args.add(i);
+            params.push(match ty {
+                ParamType::I32 => Val::I32(*arg.cast::<i32>()),
+                ParamType::F32 => Val::F32((*arg.cast::<f32>()).to_bits()),
+                ParamType::I64 => Val::I64(*arg.cast::<i64>()),
+                ParamType::F64 => Val::F64((*arg.cast::<f64>()).to_bits()),
+                ParamType::U8 => Val::I32((*arg.cast::<u8>()).into()),
+                ParamType::I8 => Val::I32((*arg.cast::<i8>()).into()),
+                ParamType::U16 => Val::I32((*arg.cast::<u16>()).into()),
+                ParamType::I16 => Val::I32((*arg.cast::<i16>()).into()),
+                ParamType::U32 => Val::I32(*arg.cast::<u32>() as i32),
+                ParamType::U64 => Val::I64(*arg.cast::<u64>() as i64),
+                ParamType::Bool => Val::I32((*arg.cast::<u8>() != 0).into()),
+                ParamType::Usize | ParamType::Isize if self.memory64 => {
+                    Val::I64(*arg.cast::<i64>())
+                }
+                ParamType::Usize => Val::I32(*arg.cast::<usize>() as i32),
+                ParamType::Isize => Val::I32(*arg.cast::<isize>() as i32),
+                ParamType::Pointer => {
+                    let memory = self.memory.as_ref().unwrap();
+                    let offset = memory.guest_offset(&*caller, *arg.cast::<usize>(), 0)?;
+                    if self.memory64 {
+                        Val::I64(offset as i64)
+                    } else {
+                        Val::I32(offset as i32)
+                    }
+                }
+                ParamType::Handle => {
+                    let handle = HandleTable::of(&mut *caller).insert(*arg.cast::<usize>());
+                    Val::I32(handle.ok_or_else(handle_table_full)? as i32)
+                }
+                _ => unreachable!("rejected when binding"),
+            });
+        }
+
+        let mut results = [Val::I32(0)];
+        let results = &mut results[..self.ty.returns.iter().len()];
+        self.func.call(&mut *caller, &params, results)?;
+
+        // Integer results are widened to a full register, as libffi expects of closures.
+        match results.first() {
+            Some(&Val::I32(val)) => result.i64 = widen(self.ty.returns.as_deref(), val),
+            Some(Val::F32(val)) => result.f32 = f32::from_bits(*val),
+            Some(Val::I64(val)) => result.i64 = *val,
+            Some(Val::F64(val)) => result.f64 = f64::from_bits(*val),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Truncates an `i32` returned by the WASM function to the native return type, then sign or zero
+/// extends it to 64 bits.
+fn widen(ty: Option<&ParamType>, val: i32) -> i64 {
+    match ty {
+        Some(ParamType::U8) => (val as u8).into(),
+        Some(ParamType::I8) => (val as i8).into(),
+        Some(ParamType::U16) => (val as u16).into(),
+        Some(ParamType::I16) => (val as i16).into(),
+        Some(ParamType::Bool) => (val != 0).into(),
+        Some(ParamType::U32 | ParamType::Usize) => (val as u32).into(),
+        _ => val.into(),
+    }
+}
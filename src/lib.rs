@@ -1,65 +1,693 @@
-use libloading::{Library, Symbol};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use libloading::Library;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use wasmparser::{BinaryReaderError, Parser, Payload};
-use wasmtime::{AsContextMut, Caller, Engine, Extern, FuncType, Linker, ValRaw, ValType};
+use wasmparser::{BinaryReaderError, CompositeInnerType, Parser, Payload, TypeRef};
+use wasmtime::{AsContextMut, Engine, FuncType, Linker, Memory, SharedMemory, Table, ValType};
 
+#[doc(hidden)]
+pub mod __private;
 mod bind;
+mod callback;
+mod manifest;
+mod store;
+mod validate;
+
+pub use manifest::{Manifest, ManifestFunction};
+pub use validate::{validate, Binding, SignatureMismatch};
+pub use wasmtime_dl_macros::import;
 
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("wasm binary error: {0}")]
     BinaryWasmError(#[from] BinaryReaderError),
+    #[error("symbol {symbol} not found in library")]
+    SymbolNotFound {
+        symbol: String,
+        #[source]
+        source: libloading::Error,
+    },
+    #[error("{module}.{name} is already defined in the linker")]
+    DuplicateDefinition { module: String, name: String },
+    #[error("{module}.{name} has a signature that cannot be bound to native code: {params:?} -> {returns:?}")]
+    UnsupportedSignature {
+        module: String,
+        name: String,
+        params: Vec<ParamType>,
+        returns: Option<ParamType>,
+    },
+    #[error("{module}.{name} is imported with types that cannot be passed to native code")]
+    UnsupportedImport {
+        module: String,
+        name: String,
+        ty: wasmparser::FuncType,
+    },
+    #[error("bindings do not match the module's imports:{}", validate::list(.0))]
+    ImportMismatch(Vec<SignatureMismatch>),
+    #[error("failed to load library {}", path.display())]
+    LibraryLoadError {
+        path: PathBuf,
+        #[source]
+        source: libloading::Error,
+    },
+    #[error("failed to read manifest {}", path.display())]
+    ManifestIoError {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("manifest TOML error: {0}")]
+    ManifestTomlError(#[from] toml::de::Error),
+    #[error("manifest JSON error: {0}")]
+    ManifestJsonError(#[from] serde_json::Error),
+}
+
+/// A function imported by a WASM module, along with its resolved type.
+#[derive(Clone, Debug)]
+pub struct FunctionImport {
+    pub module: String,
+    pub name: String,
+    pub ty: wasmparser::FuncType,
 }
 
-pub fn discover_imports(binary_module: &[u8]) -> Result<(), Error> {
+/// Lists the functions imported by `binary_module`, in the order they are imported.
+pub fn discover_imports(binary_module: &[u8]) -> Result<Vec<FunctionImport>, Error> {
+    // Indexed by type index; non-function types still take up a slot.
+    let mut types: Vec<Option<wasmparser::FuncType>> = Vec::new();
+    let mut imports = Vec::new();
     for payload in Parser::new(0).parse_all(binary_module) {
         match payload? {
             Payload::ImportSection(s) => {
                 for import in s {
                     let import = import?;
+                    let TypeRef::Func(index) = import.ty else {
+                        continue;
+                    };
+                    if let Some(Some(ty)) = types.get(index as usize) {
+                        imports.push(FunctionImport {
+                            module: import.module.to_owned(),
+                            name: import.name.to_owned(),
+                            ty: ty.clone(),
+                        });
+                    }
                 }
             }
             Payload::TypeSection(s) => {
-                for ty in s {
-                    let ty = ty?;
+                for group in s {
+                    for ty in group?.into_types() {
+                        types.push(match ty.composite_type.inner {
+                            CompositeInnerType::Func(ty) => Some(ty),
+                            _ => None,
+                        });
+                    }
                 }
             }
             _ => {}
         }
     }
 
-    Ok(())
+    Ok(imports)
 }
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ParamType {
     I32,
     F32,
     I64,
     F64,
+    /// The small integer types are passed as `i32`s, which are truncated to the native type on the
+    /// way in and sign or zero extended on the way out.
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    /// Passed as an `i64`.
+    U64,
+    /// A one-byte C `bool`, passed as an `i32` that is true if it is non-zero and returned as zero
+    /// or one.
+    Bool,
+    /// A native `size_t`, passed as an `i32` as it is in a 32-bit guest, or an `i64` with
+    /// [`BindOptions::memory64`]. It is zero extended on the way in and truncated on the way out.
+    Usize,
+    /// Like [`ParamType::Usize`], but for `ssize_t` and `ptrdiff_t`, and sign extended.
+    Isize,
+    /// An offset into the guest's memory, passed to native code as a host pointer.
+    ///
+    /// Offsets, and the lengths of [`ParamType::Str`]s, are `i32`s, or `i64`s with
+    /// [`BindOptions::memory64`]. The offset is checked against the size of the memory at the time
+    /// of the call. Returned pointers are translated back into offsets, trapping if they point
+    /// outside of the guest's memory, while null is returned as zero; use [`ParamType::Handle`] for
+    /// native objects the guest holds on to.
     Pointer,
+    /// Like [`ParamType::Pointer`], but the whole range of the given length in bytes must lie
+    /// within the guest's memory.
+    Buffer(u32),
+    /// Like [`ParamType::Pointer`], but the guest memory at the offset must hold a NUL-terminated
+    /// string.
+    ///
+    /// As a return type, the native string is copied into a buffer allocated with the binding's
+    /// [`GuestAllocator`] and its offset is returned, or zero for a null pointer. The native string
+    /// is not freed.
+    CStr,
+    /// A string passed as two `i32`s, an offset and a length in bytes, which is copied into a
+    /// NUL-terminated buffer that lives for the duration of the call. Native code sees a single
+    /// pointer, and the string is cut short if it contains a NUL. Only valid as a parameter.
+    Str,
+    /// A native pointer the guest holds as an opaque index into the store's [`HandleTable`].
+    ///
+    /// Returned pointers are added to the table, and parameters are looked up in it, trapping if
+    /// the index is not in the table. Null pointers are passed as zero in both directions.
+    Handle,
+    /// A [`ParamType::Handle`] that native code takes ownership of, such as the window passed to
+    /// `SDL_DestroyWindow`. It is removed from the table once the call returns, so later uses of
+    /// it trap. Only valid as a parameter.
+    ConsumedHandle,
+    /// A C struct with the given fields, passed by value. Fields may be numbers other than `usize`
+    /// and `isize`, whose size differs between the guest and the host, or nested structs, and are
+    /// laid out with C's rules for natural alignment. Packed structs, and structs with fields
+    /// aligned beyond their natural alignment, such as with `alignas`, are not supported.
+    ///
+    /// The guest passes an offset to the struct in its own memory, where fields are aligned to
+    /// their size. As a return type, the guest instead passes the offset to write the struct to as
+    /// an extra first parameter, and the function has no WASM result.
+    Struct(Vec<ParamType>),
+    /// An offset to a value of the given type that native code writes through. It is given a
+    /// pointer to zeroed host memory, which is copied into the guest's memory after the call. An
+    /// offset of zero is passed as a null pointer. The type may be anything a struct field can be.
+    /// Only valid as a parameter.
+    Out(Box<ParamType>),
+    /// Like [`ParamType::Out`], but the value is also copied from the guest's memory before the
+    /// call.
+    InOut(Box<ParamType>),
+    /// An index into the guest's function table, passed to native code as a C function pointer
+    /// that calls the WASM function with the given signature. Null or mistyped table entries trap
+    /// before the call. Only valid as a parameter.
+    ///
+    /// Unless it is [`CallbackType::retained`], the function pointer is freed once the call it
+    /// was passed to returns, which suits comparators but not callbacks that native code keeps.
+    /// It enters the guest through the innermost native call running on the calling thread, which
+    /// may be a later call for retained callbacks. If the WASM function traps, the callback returns
+    /// zero to native code, later callbacks during the same call return zero without entering
+    /// the guest, and the trap is raised once the native function returns. A callback called
+    /// while no native call from its store is running on the thread, such as from another thread,
+    /// returns zero without entering the guest, and the next binding called from the store traps.
+    ///
+    /// A callback may grow the guest's memory while native code holds pointers into it, such as
+    /// [`ParamType::Pointer`] arguments. Wasmtime's default configuration reserves the whole range
+    /// of a 32-bit memory up front, so such memories never move, but other memories, such as
+    /// 64-bit ones, move once they outgrow their reservation and must not be grown that far by
+    /// callbacks while native code holds pointers into them.
+    Callback(CallbackType),
+}
+
+/// The signature of a [`ParamType::Callback`].
+///
+/// Parameters may be numbers, or `pointer` or `handle`, which hand the guest a native pointer into
+/// its own memory as an offset, or any other as a [`HandleTable`] index. The return type, if any,
+/// must be a number.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct CallbackType {
+    #[serde(default)]
+    pub params: Vec<ParamType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub returns: Option<Box<ParamType>>,
+    /// Whether native code keeps the function pointer to call later, as it does event filters.
+    /// The same function pointer is then passed for the same function and signature every time,
+    /// and it stays valid until the store is dropped, before which native code must stop calling
+    /// it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub retained: bool,
+}
+
+impl CallbackType {
+    fn is_supported(&self) -> bool {
+        self.params
+            .iter()
+            .all(|ty| ty.is_number() || matches!(ty, ParamType::Pointer | ParamType::Handle))
+            && self.returns.as_deref().is_none_or(ParamType::is_number)
+    }
+
+    /// The type the WASM function must have.
+    fn func_type(&self, engine: &Engine, memory64: bool) -> FuncType {
+        FuncType::new(
+            engine,
+            self.params.iter().map(|ty| ty.val(memory64)),
+            self.returns.as_deref().map(|ty| ty.val(memory64)),
+        )
+    }
 }
 
 impl ParamType {
-    fn val(self) -> ValType {
+    /// The WASM type of this type, where offsets and sizes are `i64`s if `memory64` is set.
+    fn val(&self, memory64: bool) -> ValType {
         match self {
-            ParamType::I32 | ParamType::Pointer => ValType::I32,
+            ParamType::Usize
+            | ParamType::Isize
+            | ParamType::Pointer
+            | ParamType::Buffer(_)
+            | ParamType::CStr
+            | ParamType::Str
+            | ParamType::Struct(_)
+            | ParamType::Out(_)
+            | ParamType::InOut(_)
+                if memory64 =>
+            {
+                ValType::I64
+            }
+            ParamType::I32
+            | ParamType::U8
+            | ParamType::I8
+            | ParamType::U16
+            | ParamType::I16
+            | ParamType::U32
+            | ParamType::Bool
+            | ParamType::Usize
+            | ParamType::Isize
+            | ParamType::Pointer
+            | ParamType::Buffer(_)
+            | ParamType::CStr
+            | ParamType::Str
+            | ParamType::Handle
+            | ParamType::ConsumedHandle
+            | ParamType::Struct(_)
+            | ParamType::Out(_)
+            | ParamType::InOut(_)
+            | ParamType::Callback(_) => ValType::I32,
             ParamType::F32 => ValType::F32,
-            ParamType::I64 => ValType::I64,
+            ParamType::I64 | ParamType::U64 => ValType::I64,
             ParamType::F64 => ValType::F64,
         }
     }
+
+    /// The number of WASM values a parameter of this type is passed as, each of type `val(..)`.
+    fn width(&self) -> usize {
+        match self {
+            ParamType::Str => 2,
+            _ => 1,
+        }
+    }
+
+    fn is_return_type(&self) -> bool {
+        !matches!(
+            self,
+            ParamType::Str
+                | ParamType::ConsumedHandle
+                | ParamType::Out(_)
+                | ParamType::InOut(_)
+                | ParamType::Callback(_)
+        )
+    }
+
+    /// Whether this type is well formed, which is only in question for types built from others.
+    fn is_supported(&self) -> bool {
+        match self {
+            ParamType::Struct(fields) => {
+                !fields.is_empty() && fields.iter().all(ParamType::is_field_type)
+            }
+            ParamType::Out(ty) | ParamType::InOut(ty) => ty.is_field_type(),
+            ParamType::Callback(ty) => ty.is_supported(),
+            _ => true,
+        }
+    }
+
+    fn is_field_type(&self) -> bool {
+        match self {
+            ParamType::Usize | ParamType::Isize => false,
+            ParamType::Struct(_) => self.is_supported(),
+            ty => ty.is_number(),
+        }
+    }
+
+    fn is_number(&self) -> bool {
+        matches!(
+            self,
+            ParamType::I32
+                | ParamType::F32
+                | ParamType::I64
+                | ParamType::F64
+                | ParamType::U8
+                | ParamType::I8
+                | ParamType::U16
+                | ParamType::I16
+                | ParamType::U32
+                | ParamType::U64
+                | ParamType::Bool
+                | ParamType::Usize
+                | ParamType::Isize
+        )
+    }
+
+    fn from_wasm(ty: wasmparser::ValType) -> Option<ParamType> {
+        match ty {
+            wasmparser::ValType::I32 => Some(ParamType::I32),
+            wasmparser::ValType::F32 => Some(ParamType::F32),
+            wasmparser::ValType::I64 => Some(ParamType::I64),
+            wasmparser::ValType::F64 => Some(ParamType::F64),
+            _ => None,
+        }
+    }
+}
+
+/// The most WASM parameters a bound function can take, which is the most a WASM function type may
+/// declare.
+pub const MAX_PARAMS: usize = 1000;
+
+/// Where pointer parameters of a binding point into.
+///
+/// The memory may be a shared memory, as used by modules compiled with threads, in which case
+/// native code has the same unsynchronized access to it as the guest's own threads. Arguments are
+/// pointed into or copied in and out without synchronization, so while a call runs, other threads
+/// must not modify the memory it reads or writes, just as with a C function the guest calls
+/// itself.
+#[derive(Clone, Debug)]
+pub enum GuestMemory {
+    /// The memory exported under this name by the instance making the call. With multi-memory,
+    /// this selects which of the module's memories the binding uses, since a host function can
+    /// only reach the caller's memories through its exports.
+    ///
+    /// A memory the module imports rather than exports is selected with [`GuestMemory::Host`] or
+    /// [`GuestMemory::Shared`] instead, by passing the memory that satisfies the import. A memory
+    /// the module neither exports nor imports cannot be used by bindings.
+    Export(String),
+    /// A memory defined by the host, such as one provided to satisfy the module's memory import.
+    ///
+    /// The memory belongs to a single store, so the binding must only be called from that store.
+    Host(Memory),
+    /// A shared memory defined by the host, which can be used from every store it is imported
+    /// into.
+    Shared(SharedMemory),
+}
+
+impl Default for GuestMemory {
+    fn default() -> Self {
+        GuestMemory::Export("memory".to_owned())
+    }
+}
+
+/// The function table that [`ParamType::Callback`] indices refer to.
+#[derive(Clone, Debug)]
+pub enum GuestTable {
+    /// The table exported under this name by the instance making the call.
+    Export(String),
+    /// A table defined by the host, which belongs to a single store like [`GuestMemory::Host`].
+    Host(Table),
+}
+
+impl Default for GuestTable {
+    /// The name that LLVM exports the table under.
+    fn default() -> Self {
+        GuestTable::Export("__indirect_function_table".to_owned())
+    }
+}
+
+/// A function exported by the guest that allocates memory for values returned to it.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GuestAllocator {
+    /// An export with the signature of `malloc`, `(size: i32) -> i32`.
+    Malloc(String),
+    /// An export with the signature of the component model's `cabi_realloc`,
+    /// `(old_ptr: i32, old_size: i32, align: i32, new_size: i32) -> i32`.
+    Realloc(String),
+}
+
+impl Default for GuestAllocator {
+    fn default() -> Self {
+        GuestAllocator::Malloc("malloc".to_owned())
+    }
+}
+
+/// Options for [`bind_with_options`].
+#[derive(Clone, Debug, Default)]
+pub struct BindOptions {
+    /// The memory that [`ParamType::Pointer`] and [`ParamType::Buffer`] offsets refer to. Calls
+    /// trap if the memory cannot be found.
+    pub memory: GuestMemory,
+    /// Whether `memory` is a 64-bit memory, whose offsets and sizes are passed as `i64`s. The
+    /// allocator then takes and returns `i64`s as well.
+    pub memory64: bool,
+    /// The allocator that returned [`ParamType::CStr`] strings are copied into. Calls that return
+    /// a string trap if it cannot be found.
+    pub allocator: GuestAllocator,
+    /// The table that [`ParamType::Callback`] indices refer to. Calls trap if the table cannot be
+    /// found.
+    pub table: GuestTable,
+    /// For a variadic function, the number of parameters before the `...`. The remaining
+    /// parameters are passed as variadic arguments, with `f32`s promoted to `double` as C
+    /// requires, so each binding fixes the types of the variadic arguments it is called with.
+    pub fixed_params: Option<usize>,
+}
+
+/// Native pointers handed to the guest as 32-bit indices, for [`ParamType::Handle`].
+///
+/// Each store has its own table, which every binding called from the store shares, so handles
+/// can be passed between bindings but not between stores. Cloning a table gives another
+/// reference to the same table.
+///
+/// A table holds up to 2^20 - 1 pointers at a time. Removing a handle frees its slot for reuse,
+/// under a new handle: the high 12 bits of a handle count how often its slot has been reused, so a
+/// removed handle stops resolving, at least until its slot has been reused another 4096 times.
+#[derive(Clone, Debug, Default)]
+pub struct HandleTable(Arc<Mutex<Handles>>);
+
+/// The number of low bits of a handle that hold its slot index plus one, so that zero stays null.
+const HANDLE_INDEX_BITS: u32 = 20;
+const HANDLE_INDEX_MASK: u32 = (1 << HANDLE_INDEX_BITS) - 1;
+
+#[derive(Debug, Default)]
+struct Handles {
+    slots: Vec<HandleSlot>,
+    /// Empty slots, reused oldest first so that their generations wrap as late as possible.
+    free: VecDeque<u32>,
+    indices: HashMap<usize, u32>,
+}
+
+#[derive(Debug)]
+struct HandleSlot {
+    ptr: Option<usize>,
+    generation: u32,
+}
+
+impl Handles {
+    /// The index of the slot `handle` refers to, if the slot hasn't been reused since.
+    fn index(&self, handle: u32) -> Option<usize> {
+        let index = (handle & HANDLE_INDEX_MASK).checked_sub(1)? as usize;
+        let slot = self.slots.get(index)?;
+        (slot.generation == handle >> HANDLE_INDEX_BITS).then_some(index)
+    }
+}
+
+impl HandleTable {
+    /// The table of `store`, created the first time it is asked for.
+    pub fn of<T: 'static>(store: impl AsContextMut<Data = T>) -> HandleTable {
+        store::state(store).handles.clone()
+    }
+
+    /// Returns the handle for `ptr`, adding it to the table if it isn't already there, or `None`
+    /// if the table is full.
+    pub fn insert(&self, ptr: usize) -> Option<u32> {
+        if ptr == 0 {
+            return Some(0);
+        }
+        let mut handles = self.0.lock().unwrap();
+        if let Some(&handle) = handles.indices.get(&ptr) {
+            return Some(handle);
+        }
+        let index = match handles.free.pop_front() {
+            Some(index) => index,
+            None => {
+                let index = u32::try_from(handles.slots.len()).ok()?;
+                if index >= HANDLE_INDEX_MASK {
+                    return None;
+                }
+                handles.slots.push(HandleSlot {
+                    ptr: None,
+                    generation: 0,
+                });
+                index
+            }
+        };
+        let slot = &mut handles.slots[index as usize];
+        slot.ptr = Some(ptr);
+        let handle = slot.generation << HANDLE_INDEX_BITS | (index + 1);
+        handles.indices.insert(ptr, handle);
+        Some(handle)
+    }
+
+    /// Looks up the pointer for `handle`.
+    pub fn get(&self, handle: u32) -> Option<usize> {
+        if handle == 0 {
+            return Some(0);
+        }
+        let handles = self.0.lock().unwrap();
+        handles.slots[handles.index(handle)?].ptr
+    }
+
+    /// Removes `handle` from the table, so that later uses of it trap, and returns its pointer.
+    pub fn remove(&self, handle: u32) -> Option<usize> {
+        let mut handles = self.0.lock().unwrap();
+        let index = handles.index(handle)?;
+        let slot = &mut handles.slots[index];
+        let ptr = slot.ptr.take()?;
+        slot.generation = (slot.generation + 1) & (u32::MAX >> HANDLE_INDEX_BITS);
+        handles.free.push_back(index as u32);
+        handles.indices.remove(&ptr);
+        Some(ptr)
+    }
+}
+
+/// Binds the native function `lib_name` from `lib` as the WASM import `module`.`name`.
+///
+/// The native return value, if any, is converted according to `returns` and handed back to
+/// the WASM caller. The binding keeps `lib` loaded until the linker and every store it was
+/// instantiated into have been dropped.
+///
+/// Functions may take up to [`MAX_PARAMS`] WASM parameters, counting the pointer a returned struct
+/// is written through; longer signatures, and return types that are only valid as parameters, are
+/// rejected with [`Error::UnsupportedSignature`].
+/// Variadic functions are bound with [`bind_with_options`] and [`BindOptions::fixed_params`].
+///
+/// # Safety
+///
+/// The symbol must be a C function whose signature matches `params` and `returns`.
+pub unsafe fn bind<T: 'static>(
+    linker: &mut Linker<T>,
+    module: &str,
+    name: &str,
+    lib: &Arc<Library>,
+    lib_name: &[u8],
+    params: &[ParamType],
+    returns: Option<ParamType>,
+) -> Result<(), Error> {
+    bind_with_options(
+        linker,
+        module,
+        name,
+        lib,
+        lib_name,
+        params,
+        returns,
+        &BindOptions::default(),
+    )
 }
 
-pub unsafe fn bind(
-    linker: &mut Linker<()>,
+/// Like [`bind`], but with non-default [`BindOptions`].
+///
+/// # Safety
+///
+/// The symbol must be a C function whose signature matches `params` and `returns`.
+#[allow(clippy::too_many_arguments)]
+pub unsafe fn bind_with_options<T: 'static>(
+    linker: &mut Linker<T>,
     module: &str,
     name: &str,
-    lib: &'static Library,
+    lib: &Arc<Library>,
     lib_name: &[u8],
     params: &[ParamType],
     returns: Option<ParamType>,
-) {
-    let ty = FuncType::new(linker.engine(), params.iter().map(|p| p.val()), []);
-    bind::bind(linker, module, name, lib, lib_name, ty, params, returns);
+    options: &BindOptions,
+) -> Result<(), Error> {
+    // A returned struct takes a pointer parameter of its own.
+    let ret_ptr = usize::from(matches!(returns, Some(ParamType::Struct(_))));
+    let width = ret_ptr + params.iter().map(|p| p.width()).sum::<usize>();
+    let supported = params.iter().all(ParamType::is_supported)
+        && returns
+            .as_ref()
+            .is_none_or(|r| r.is_supported() && r.is_return_type());
+    let fixed = options
+        .fixed_params
+        .is_none_or(|fixed| fixed <= params.len());
+    if width > MAX_PARAMS || !supported || !fixed {
+        return Err(Error::UnsupportedSignature {
+            module: module.to_owned(),
+            name: name.to_owned(),
+            params: params.to_vec(),
+            returns,
+        });
+    }
+    let ty = func_type(linker.engine(), params, returns.as_ref(), options.memory64);
+    bind::bind(
+        linker, module, name, lib, lib_name, ty, params, returns, options,
+    )
+}
+
+/// The WASM type of a binding with the given parameters and return type.
+fn func_type(
+    engine: &Engine,
+    params: &[ParamType],
+    returns: Option<&ParamType>,
+    memory64: bool,
+) -> FuncType {
+    // Structs are returned through a pointer passed before the other parameters.
+    let (ret_ptr, returns) = match returns {
+        Some(ty @ ParamType::Struct(_)) => (Some(ty.val(memory64)), None),
+        returns => (None, returns),
+    };
+    FuncType::new(
+        engine,
+        ret_ptr.into_iter().chain(
+            params
+                .iter()
+                .flat_map(|p| std::iter::repeat_n(p.val(memory64), p.width())),
+        ),
+        returns.map(|ty| ty.val(memory64)),
+    )
+}
+
+/// Binds every function that `binary_module` imports from `module` to the symbol of the same name
+/// in `lib`.
+///
+/// Parameter and return types are taken from the import, so pointers are passed to native code as
+/// plain `i32` offsets; use [`bind`] for functions that take pointers into guest memory. A name
+/// imported more than once is bound once, from its first import.
+///
+/// # Safety
+///
+/// Every imported symbol must be a C function whose signature matches the import.
+pub unsafe fn bind_all<T: 'static>(
+    linker: &mut Linker<T>,
+    binary_module: &[u8],
+    lib: &Arc<Library>,
+    module: &str,
+) -> Result<(), Error> {
+    let mut bound = HashSet::new();
+    for import in discover_imports(binary_module)? {
+        if import.module != module || !bound.insert(import.name.clone()) {
+            continue;
+        }
+        let unsupported = || Error::UnsupportedImport {
+            module: import.module.clone(),
+            name: import.name.clone(),
+            ty: import.ty.clone(),
+        };
+        let params = import
+            .ty
+            .params()
+            .iter()
+            .map(|ty| ParamType::from_wasm(*ty))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(unsupported)?;
+        let returns = match import.ty.results() {
+            [] => None,
+            [ty] => Some(ParamType::from_wasm(*ty).ok_or_else(unsupported)?),
+            _ => return Err(unsupported()),
+        };
+        bind(
+            linker,
+            module,
+            &import.name,
+            lib,
+            import.name.as_bytes(),
+            &params,
+            returns,
+        )?;
+    }
+
+    Ok(())
 }
@@ -0,0 +1,165 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use libloading::Library;
+use serde::{Deserialize, Serialize};
+use wasmtime::Linker;
+
+use crate::{
+    bind_with_options, BindOptions, Binding, Error, GuestAllocator, GuestMemory, ParamType,
+};
+
+/// A set of native functions to bind from a single library, described in a TOML or JSON file.
+///
+/// ```toml
+/// library = "libs/libSDL2.so"
+/// module = "sdl"
+///
+/// [[functions]]
+/// name = "SDL_GetTicks"
+/// returns = "i32"
+///
+/// [[functions]]
+/// name = "set_color"
+/// symbol = "SDL_SetRenderDrawColor"
+/// params = ["pointer", "i32", "i32", "i32", "i32"]
+/// returns = "i32"
+/// ```
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// The library to load. When loaded from a file, relative paths with more than one component
+    /// are resolved against the manifest's directory; bare file names are left to the system's
+    /// library search.
+    pub library: PathBuf,
+    /// The WASM module namespace the functions are imported from.
+    pub module: String,
+    /// The memory export that pointer parameters refer to, if not `memory`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<String>,
+    /// Whether the memories the functions use are 64-bit; see
+    /// [`BindOptions::memory64`](crate::BindOptions::memory64).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub memory64: bool,
+    /// The guest allocator that returned strings are copied into, if not `malloc`, written as
+    /// `{ malloc = "name" }` or `{ realloc = "name" }`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allocator: Option<GuestAllocator>,
+    #[serde(default)]
+    pub functions: Vec<ManifestFunction>,
+}
+
+/// A single function in a [`Manifest`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestFunction {
+    /// The name the function is imported under.
+    pub name: String,
+    /// The native symbol, if it differs from `name`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    /// The memory export this function's pointer parameters refer to, if not the manifest's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<String>,
+    #[serde(default)]
+    pub params: Vec<ParamType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub returns: Option<ParamType>,
+    /// For a variadic function, the number of `params` before the `...`; see
+    /// [`BindOptions::fixed_params`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fixed_params: Option<usize>,
+}
+
+impl Manifest {
+    /// Reads a manifest from `path`, which is parsed as JSON if it has a `.json` extension and as
+    /// TOML otherwise.
+    pub fn load(path: impl AsRef<Path>) -> Result<Manifest, Error> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|source| Error::ManifestIoError {
+            path: path.to_owned(),
+            source,
+        })?;
+        let mut manifest = if path.extension().is_some_and(|ext| ext == "json") {
+            Manifest::from_json(&contents)?
+        } else {
+            Manifest::from_toml(&contents)?
+        };
+
+        if manifest.library.is_relative() && manifest.library.components().count() > 1 {
+            if let Some(dir) = path.parent() {
+                manifest.library = dir.join(&manifest.library);
+            }
+        }
+
+        Ok(manifest)
+    }
+
+    pub fn from_toml(contents: &str) -> Result<Manifest, Error> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    pub fn from_json(contents: &str) -> Result<Manifest, Error> {
+        Ok(serde_json::from_str(contents)?)
+    }
+
+    /// The imports this manifest defines, for checking against a module with
+    /// [`validate`](crate::validate).
+    pub fn bindings(&self) -> Vec<Binding> {
+        self.functions
+            .iter()
+            .map(|function| Binding {
+                module: self.module.clone(),
+                name: function.name.clone(),
+                params: function.params.clone(),
+                returns: function.returns.clone(),
+                memory64: self.memory64,
+            })
+            .collect()
+    }
+
+    /// Loads the library and binds every function in the manifest into `linker`.
+    ///
+    /// # Safety
+    ///
+    /// Loading the library runs its initialization code, and every function must be a C function
+    /// whose signature matches its declared parameters and return type.
+    pub unsafe fn apply<T: 'static>(&self, linker: &mut Linker<T>) -> Result<(), Error> {
+        let lib = Library::new(&self.library).map_err(|source| Error::LibraryLoadError {
+            path: self.library.clone(),
+            source,
+        })?;
+        let lib = Arc::new(lib);
+        let options = BindOptions {
+            memory64: self.memory64,
+            allocator: self.allocator.clone().unwrap_or_default(),
+            ..BindOptions::default()
+        };
+
+        for function in &self.functions {
+            let symbol = function.symbol.as_ref().unwrap_or(&function.name);
+            bind_with_options(
+                linker,
+                &self.module,
+                &function.name,
+                &lib,
+                symbol.as_bytes(),
+                &function.params,
+                function.returns.clone(),
+                &BindOptions {
+                    memory: function
+                        .memory
+                        .as_ref()
+                        .or(self.memory.as_ref())
+                        .cloned()
+                        .map_or_else(GuestMemory::default, GuestMemory::Export),
+                    fixed_params: function.fixed_params,
+                    ..options.clone()
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+
+use wasmtime::{AsContext, AsContextMut, Func};
+
+use crate::callback::{Callback, RetainedKey};
+use crate::HandleTable;
+
+/// State that bindings keep for each store they are called from, until the store is dropped.
+#[derive(Default)]
+pub(crate) struct StoreState {
+    pub(crate) handles: HandleTable,
+    /// Retained [`ParamType::Callback`](crate::ParamType::Callback)s.
+    pub(crate) callbacks: Mutex<HashMap<RetainedKey, Callback>>,
+    pub(crate) stray_error: Arc<StrayError>,
+}
+
+/// The first error from a callback that was called while no native call from its store was
+/// running on the thread, which is raised by the next binding called from the store.
+#[derive(Default)]
+pub(crate) struct StrayError(Mutex<Option<wasmtime::Error>>);
+
+/// The number of [`StrayError`]s that hold an error, so that bindings only look up their store's
+/// when there may be one.
+static STRAY_ERRORS: AtomicUsize = AtomicUsize::new(0);
+
+impl StrayError {
+    pub(crate) fn report(&self, error: wasmtime::Error) {
+        let mut slot = self.0.lock().unwrap();
+        if slot.is_none() {
+            *slot = Some(error);
+            STRAY_ERRORS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn take(&self) -> Option<wasmtime::Error> {
+        let error = self.0.lock().unwrap().take()?;
+        STRAY_ERRORS.fetch_sub(1, Ordering::Relaxed);
+        Some(error)
+    }
+}
+
+impl Drop for StrayError {
+    fn drop(&mut self) {
+        self.take();
+    }
+}
+
+/// Takes the error from a callback that was called outside of a native call from `store`.
+pub(crate) fn take_stray_error<T: 'static>(
+    store: impl AsContextMut<Data = T>,
+) -> Option<wasmtime::Error> {
+    if STRAY_ERRORS.load(Ordering::Relaxed) == 0 {
+        return None;
+    }
+    state(store).stray_error.take()
+}
+
+/// The state of live stores, keyed by [`key`].
+static STORES: LazyLock<Mutex<HashMap<StoreKey, Arc<StoreState>>>> =
+    LazyLock::new(Default::default);
+
+/// Removes a store's state once the store, which owns it through a host function, is dropped.
+struct StoreGuard(StoreKey);
+
+impl Drop for StoreGuard {
+    fn drop(&mut self) {
+        let state = STORES.lock().unwrap().remove(&self.0);
+        // Dropped outside of the lock, since freeing callbacks may take a while.
+        drop(state);
+    }
+}
+
+/// Identifies a live store by the type and address of its data.
+///
+/// Every store keeps its data at the same offset of its own allocation, which lives as long as the
+/// store, so stores with the same data type never share an address, even when the data is
+/// zero-sized and sits at the end of the allocation. Stores with different data types may, which
+/// is why the type is part of the key.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct StoreKey {
+    ty: TypeId,
+    data: usize,
+}
+
+pub(crate) fn key<T: 'static>(store: impl AsContext<Data = T>) -> StoreKey {
+    StoreKey {
+        ty: TypeId::of::<T>(),
+        data: store.as_context().data() as *const T as usize,
+    }
+}
+
+/// The state of `store`, created the first time it is asked for.
+pub(crate) fn state<T: 'static>(mut store: impl AsContextMut<Data = T>) -> Arc<StoreState> {
+    let key = key(&store);
+    let mut stores = STORES.lock().unwrap();
+    if let Some(state) = stores.get(&key) {
+        return Arc::clone(state);
+    }
+    let state = Arc::new(StoreState::default());
+    stores.insert(key, Arc::clone(&state));
+    drop(stores);
+    let guard = StoreGuard(key);
+    Func::wrap(&mut store, move || {
+        let _guard = &guard;
+    });
+    state
+}
@@ -0,0 +1,83 @@
+use std::fmt;
+
+use wasmtime::{ExternType, FuncType, Module};
+
+use crate::{func_type, Error, ParamType};
+
+/// The WASM import a native function is bound to, and the types it is bound with.
+#[derive(Clone, Debug)]
+pub struct Binding {
+    pub module: String,
+    pub name: String,
+    pub params: Vec<ParamType>,
+    pub returns: Option<ParamType>,
+    /// Whether pointers are bound as offsets into a 64-bit memory; see
+    /// [`BindOptions::memory64`](crate::BindOptions::memory64).
+    pub memory64: bool,
+}
+
+/// A binding whose type does not match the type the module imports it with.
+#[derive(Clone, Debug)]
+pub struct SignatureMismatch {
+    pub module: String,
+    pub name: String,
+    /// The type the module imports the function with.
+    pub expected: FuncType,
+    /// The type derived from the binding's parameters and return type.
+    pub found: FuncType,
+}
+
+impl fmt::Display for SignatureMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{} is imported as {} but bound as {}",
+            self.module, self.name, self.expected, self.found
+        )
+    }
+}
+
+/// Checks every binding against the type `module` imports it with, returning
+/// [`Error::ImportMismatch`] with all of the mismatches if any are found.
+///
+/// Bindings the module doesn't import, and imports with no binding, are ignored.
+pub fn validate(module: &Module, bindings: &[Binding]) -> Result<(), Error> {
+    let mut mismatches = Vec::new();
+    for import in module.imports() {
+        let ExternType::Func(expected) = import.ty() else {
+            continue;
+        };
+        for binding in bindings {
+            if binding.module != import.module() || binding.name != import.name() {
+                continue;
+            }
+            let found = func_type(
+                module.engine(),
+                &binding.params,
+                binding.returns.as_ref(),
+                binding.memory64,
+            );
+            if !found.matches(&expected) {
+                mismatches.push(SignatureMismatch {
+                    module: binding.module.clone(),
+                    name: binding.name.clone(),
+                    expected: expected.clone(),
+                    found,
+                });
+            }
+        }
+    }
+
+    if mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::ImportMismatch(mismatches))
+    }
+}
+
+pub(crate) fn list(mismatches: &[SignatureMismatch]) -> String {
+    mismatches
+        .iter()
+        .map(|mismatch| format!("\n  {mismatch}"))
+        .collect()
+}
@@ -0,0 +1,60 @@
+use wasmtime::{Engine, Linker, Module, Store, Val};
+use wasmtime_dl::{bind, ParamType};
+
+mod common;
+
+const MODULE: &str = r#"
+    (module
+        (type $sum_many (func
+            (param i32 f32 i64 f64 i32 f32 i64 f64 i32 f32 i64 f64 i32 f32 i64 f64 i32 f64)
+            (result f64)))
+        (import "native" "sum_many" (func $sum_many (type $sum_many)))
+        (export "sum_many" (func $sum_many)))
+"#;
+
+#[test]
+fn passes_arguments_beyond_the_argument_registers() {
+    let engine = Engine::default();
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::new(&engine);
+    let mut params: Vec<_> = [
+        ParamType::I32,
+        ParamType::F32,
+        ParamType::I64,
+        ParamType::F64,
+    ]
+    .into_iter()
+    .cycle()
+    .take(16)
+    .collect();
+    params.extend([ParamType::I32, ParamType::F64]);
+    unsafe {
+        bind(
+            &mut linker,
+            "native",
+            "sum_many",
+            &common::library(),
+            b"sum_many",
+            &params,
+            Some(ParamType::F64),
+        )
+        .unwrap();
+    }
+    let module = Module::new(&engine, MODULE).unwrap();
+    let instance = linker.instantiate(&mut store, &module).unwrap();
+    let sum_many = instance.get_func(&mut store, "sum_many").unwrap();
+
+    let args: Vec<_> = params
+        .iter()
+        .zip(1..)
+        .map(|(ty, i)| match ty {
+            ParamType::I32 => Val::I32(i),
+            ParamType::F32 => Val::F32((i as f32).to_bits()),
+            ParamType::I64 => Val::I64(i.into()),
+            _ => Val::F64((i as f64).to_bits()),
+        })
+        .collect();
+    let mut result = [Val::F64(0)];
+    sum_many.call(&mut store, &args, &mut result).unwrap();
+    assert_eq!(result[0].unwrap_f64(), 171.0);
+}
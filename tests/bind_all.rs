@@ -0,0 +1,97 @@
+use wasmtime::{Engine, Linker, Module, Store};
+use wasmtime_dl::{bind_all, discover_imports, Error};
+
+mod common;
+
+const MODULE: &str = r#"
+    (module
+        (import "native" "echo_f32" (func $echo_f32 (param f32) (result f32)))
+        (import "host" "log" (func $log (param i32)))
+        (import "native" "sum_mixed"
+            (func $sum_mixed (param i32 f32 i64 f64 f32) (result f64)))
+        (func (export "sum_mixed") (param i32 f32 i64 f64 f32) (result f64)
+            (call $sum_mixed
+                (local.get 0) (local.get 1) (local.get 2) (local.get 3) (local.get 4))))
+"#;
+
+#[test]
+fn discovers_function_imports() {
+    let binary = wat::parse_str(MODULE).unwrap();
+    let imports = discover_imports(&binary).unwrap();
+
+    let names: Vec<_> = imports
+        .iter()
+        .map(|import| (import.module.as_str(), import.name.as_str()))
+        .collect();
+    assert_eq!(
+        names,
+        [
+            ("native", "echo_f32"),
+            ("host", "log"),
+            ("native", "sum_mixed")
+        ]
+    );
+    assert_eq!(imports[2].ty.params().len(), 5);
+    assert_eq!(imports[2].ty.results(), [wasmparser::ValType::F64]);
+}
+
+#[test]
+fn binds_every_import_of_a_module() {
+    let binary = wat::parse_str(MODULE).unwrap();
+    let engine = Engine::default();
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::new(&engine);
+    unsafe { bind_all(&mut linker, &binary, &common::library(), "native").unwrap() };
+    linker.func_wrap("host", "log", |_: i32| {}).unwrap();
+
+    let module = Module::new(&engine, &binary).unwrap();
+    let instance = linker.instantiate(&mut store, &module).unwrap();
+    let sum_mixed = instance
+        .get_typed_func::<(i32, f32, i64, f64, f32), f64>(&mut store, "sum_mixed")
+        .unwrap();
+    assert_eq!(
+        sum_mixed.call(&mut store, (1, 2.0, 3, 4.0, 5.0)).unwrap(),
+        15.0
+    );
+}
+
+#[test]
+fn binds_names_imported_twice_once() {
+    let binary = wat::parse_str(
+        r#"
+        (module
+            (import "native" "echo_f32" (func $first (param f32) (result f32)))
+            (import "native" "echo_f32" (func $second (param f32) (result f32)))
+            (func (export "echo_twice") (param f32) (result f32)
+                (call $second (call $first (local.get 0)))))
+        "#,
+    )
+    .unwrap();
+    let engine = Engine::default();
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::new(&engine);
+    unsafe { bind_all(&mut linker, &binary, &common::library(), "native").unwrap() };
+
+    let module = Module::new(&engine, &binary).unwrap();
+    let instance = linker.instantiate(&mut store, &module).unwrap();
+    let echo_twice = instance
+        .get_typed_func::<f32, f32>(&mut store, "echo_twice")
+        .unwrap();
+    assert_eq!(echo_twice.call(&mut store, 1.5).unwrap(), 1.5);
+}
+
+#[test]
+fn rejects_imports_native_code_cannot_take() {
+    let binary = wat::parse_str(
+        r#"(module (import "native" "echo_f32" (func (param externref) (result f32))))"#,
+    )
+    .unwrap();
+    let engine = Engine::default();
+    let mut linker = Linker::<()>::new(&engine);
+
+    let result = unsafe { bind_all(&mut linker, &binary, &common::library(), "native") };
+    assert!(matches!(
+        result,
+        Err(Error::UnsupportedImport { name, .. }) if name == "echo_f32"
+    ));
+}
@@ -0,0 +1,309 @@
+use std::thread;
+
+use wasmtime::{Engine, Linker, Memory, Store, Trap, TypedFunc};
+use wasmtime_dl::{bind, CallbackType, Error, ParamType};
+
+mod common;
+
+use common::Function;
+
+const MODULE: &str = r#"
+    (module
+        (import "native" "sort_i32" (func $sort_i32 (param i32 i32 i32)))
+        (import "native" "apply_twice" (func $apply_twice (param i32 f64) (result f64)))
+        (memory (export "memory") 1)
+        (table (export "__indirect_function_table") 5 funcref)
+        (elem (i32.const 1) $ascending $descending $scale $growing)
+        (func $ascending (param i32 i32) (result i32)
+            (i32.sub (i32.load (local.get 0)) (i32.load (local.get 1))))
+        (func $descending (param i32 i32) (result i32)
+            (i32.sub (i32.load (local.get 1)) (i32.load (local.get 0))))
+        (func $growing (param i32 i32) (result i32)
+            (drop (memory.grow (i32.const 1)))
+            (call $ascending (local.get 0) (local.get 1)))
+        (func $scale (param f64 i64) (result f64)
+            (if (i64.gt_s (local.get 1) (i64.const 1)) (then unreachable))
+            (f64.mul (local.get 0) (f64.convert_i64_s (local.get 1))))
+        (func (export "sort_i32") (param i32 i32 i32)
+            (call $sort_i32 (local.get 0) (local.get 1) (local.get 2)))
+        (func (export "apply_twice") (param i32 f64) (result f64)
+            (call $apply_twice (local.get 0) (local.get 1))))
+"#;
+
+fn comparator() -> ParamType {
+    ParamType::Callback(CallbackType {
+        params: vec![ParamType::Pointer, ParamType::Pointer],
+        returns: Some(Box::new(ParamType::I32)),
+        ..Default::default()
+    })
+}
+
+fn functions() -> Vec<Function<'static>> {
+    let scale = ParamType::Callback(CallbackType {
+        params: vec![ParamType::F64, ParamType::I64],
+        returns: Some(Box::new(ParamType::F64)),
+        ..Default::default()
+    });
+    vec![
+        (
+            "sort_i32",
+            vec![ParamType::Pointer, ParamType::I32, comparator()],
+            None,
+        ),
+        (
+            "apply_twice",
+            vec![scale, ParamType::F64],
+            Some(ParamType::F64),
+        ),
+    ]
+}
+
+fn write_items(store: &mut Store<()>, memory: Memory, items: &[i32]) {
+    let bytes: Vec<u8> = items.iter().flat_map(|item| item.to_le_bytes()).collect();
+    memory.write(store, 16, &bytes).unwrap();
+}
+
+fn read_items(store: &Store<()>, memory: Memory, len: usize) -> Vec<i32> {
+    memory.data(store)[16..16 + len * 4]
+        .chunks(4)
+        .map(|chunk| i32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect()
+}
+
+#[test]
+fn calls_wasm_functions_from_native_code() {
+    let (mut store, instance) = common::instantiate(MODULE, &functions());
+    let memory = common::memory(&mut store, &instance);
+    let sort = instance
+        .get_typed_func::<(i32, i32, i32), ()>(&mut store, "sort_i32")
+        .unwrap();
+
+    write_items(&mut store, memory, &[3, -1, 4, 1, 5]);
+    sort.call(&mut store, (16, 5, 1)).unwrap();
+    assert_eq!(read_items(&store, memory, 5), [-1, 1, 3, 4, 5]);
+
+    sort.call(&mut store, (16, 5, 2)).unwrap();
+    assert_eq!(read_items(&store, memory, 5), [5, 4, 3, 1, -1]);
+}
+
+#[test]
+fn keeps_pointers_valid_when_callbacks_grow_memory() {
+    let (mut store, instance) = common::instantiate(MODULE, &functions());
+    let memory = common::memory(&mut store, &instance);
+    let sort = instance
+        .get_typed_func::<(i32, i32, i32), ()>(&mut store, "sort_i32")
+        .unwrap();
+
+    write_items(&mut store, memory, &[3, -1, 4, 1, 5]);
+    sort.call(&mut store, (16, 5, 4)).unwrap();
+    assert_eq!(read_items(&store, memory, 5), [-1, 1, 3, 4, 5]);
+    assert!(memory.size(&store) > 1);
+}
+
+#[test]
+fn traps_on_null_and_mistyped_table_entries() {
+    let (mut store, instance) = common::instantiate(MODULE, &functions());
+    let sort = instance
+        .get_typed_func::<(i32, i32, i32), ()>(&mut store, "sort_i32")
+        .unwrap();
+
+    let err = sort.call(&mut store, (16, 2, 0)).unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Trap::IndirectCallToNull));
+    let err = sort.call(&mut store, (16, 2, 3)).unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Trap::BadSignature));
+    let err = sort.call(&mut store, (16, 2, 5)).unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Trap::TableOutOfBounds));
+}
+
+#[test]
+fn propagates_traps_in_callbacks_after_the_native_call() {
+    let (mut store, instance) = common::instantiate(MODULE, &functions());
+    let apply_twice = instance
+        .get_typed_func::<(i32, f64), f64>(&mut store, "apply_twice")
+        .unwrap();
+
+    // The second call traps, so the first one's result never reaches the guest.
+    let err = apply_twice.call(&mut store, (3, 1.5)).unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Trap::UnreachableCodeReached));
+}
+
+#[test]
+fn rejects_callbacks_returning_pointers() {
+    let engine = Engine::default();
+    let mut linker = Linker::<()>::new(&engine);
+
+    let callback = ParamType::Callback(CallbackType {
+        params: Vec::new(),
+        returns: Some(Box::new(ParamType::Pointer)),
+        ..Default::default()
+    });
+    let result = unsafe {
+        bind(
+            &mut linker,
+            "native",
+            "sort_i32",
+            &common::library(),
+            b"sort_i32",
+            &[ParamType::Pointer, ParamType::I32, callback],
+            None,
+        )
+    };
+    assert!(matches!(result, Err(Error::UnsupportedSignature { .. })));
+}
+
+const RETAINED_MODULE: &str = r#"
+    (module
+        (import "native" "set_filter" (func $set_filter (param i32 i32) (result i32)))
+        (import "native" "run_filter" (func $run_filter (param i32 i32) (result i32)))
+        (table (export "__indirect_function_table") 3 funcref)
+        (elem (i32.const 1) $double $trap)
+        (func $double (param i32) (result i32)
+            (i32.mul (local.get 0) (i32.const 2)))
+        (func $trap (param i32) (result i32)
+            unreachable)
+        (func (export "set_filter") (param i32 i32) (result i32)
+            (call $set_filter (local.get 0) (local.get 1)))
+        (func (export "run_filter") (param i32 i32) (result i32)
+            (call $run_filter (local.get 0) (local.get 1))))
+"#;
+
+struct Filters {
+    store: Store<()>,
+    set: TypedFunc<(i32, i32), i32>,
+    run: TypedFunc<(i32, i32), i32>,
+}
+
+/// Each test uses its own filter slot, since the native library is shared between them.
+fn filters() -> Filters {
+    let filter = ParamType::Callback(CallbackType {
+        params: vec![ParamType::I32],
+        returns: Some(Box::new(ParamType::I32)),
+        retained: true,
+    });
+    let (mut store, instance) = common::instantiate(
+        RETAINED_MODULE,
+        &[
+            (
+                "set_filter",
+                vec![ParamType::I32, filter],
+                Some(ParamType::Bool),
+            ),
+            (
+                "run_filter",
+                vec![ParamType::I32, ParamType::I32],
+                Some(ParamType::I32),
+            ),
+        ],
+    );
+    Filters {
+        set: instance.get_typed_func(&mut store, "set_filter").unwrap(),
+        run: instance.get_typed_func(&mut store, "run_filter").unwrap(),
+        store,
+    }
+}
+
+#[test]
+fn keeps_retained_callbacks_for_later_calls() {
+    let Filters {
+        mut store,
+        set,
+        run,
+    } = filters();
+
+    assert_eq!(set.call(&mut store, (0, 1)).unwrap(), 0);
+    assert_eq!(run.call(&mut store, (0, 21)).unwrap(), 42);
+    // Setting the same function again passes the same function pointer.
+    assert_eq!(set.call(&mut store, (0, 1)).unwrap(), 1);
+    assert_eq!(set.call(&mut store, (0, 2)).unwrap(), 0);
+}
+
+#[test]
+fn propagates_traps_in_retained_callbacks() {
+    let Filters {
+        mut store,
+        set,
+        run,
+    } = filters();
+
+    set.call(&mut store, (1, 2)).unwrap();
+    let err = run.call(&mut store, (1, 0)).unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Trap::UnreachableCodeReached));
+}
+
+#[test]
+fn traps_after_callbacks_from_other_threads() {
+    let Filters {
+        mut store,
+        set,
+        run,
+    } = filters();
+
+    set.call(&mut store, (2, 1)).unwrap();
+    let lib = common::library();
+    let native_run = unsafe {
+        *lib.get::<unsafe extern "C" fn(i32, i32) -> i32>(b"run_filter")
+            .unwrap()
+    };
+    let result = thread::spawn(move || unsafe { native_run(2, 21) })
+        .join()
+        .unwrap();
+    assert_eq!(result, 0);
+
+    let err = run.call(&mut store, (2, 21)).unwrap_err();
+    assert!(format!("{err:?}").contains("no native call"), "{err:?}");
+    assert_eq!(run.call(&mut store, (2, 21)).unwrap(), 42);
+}
+
+#[test]
+fn keeps_the_memory_of_the_instance_that_passed_a_retained_callback() {
+    let filter = ParamType::Callback(CallbackType {
+        params: vec![ParamType::Pointer],
+        returns: Some(Box::new(ParamType::I32)),
+        retained: true,
+    });
+    let linker = common::linker(
+        &Engine::default(),
+        &[
+            ("set_pointer_filter", vec![filter, ParamType::Pointer], None),
+            ("run_pointer_filter", Vec::new(), Some(ParamType::I32)),
+        ],
+        &Default::default(),
+    );
+    let (mut store, first) = common::instantiate_with(
+        &linker,
+        r#"
+        (module
+            (import "native" "set_pointer_filter" (func $set (param i32 i32)))
+            (memory (export "memory") 1)
+            (table (export "__indirect_function_table") 2 funcref)
+            (elem (i32.const 1) $read)
+            (data (i32.const 16) "\2a")
+            (func $read (param i32) (result i32)
+                (i32.load (local.get 0)))
+            (func (export "set") (param i32 i32)
+                (call $set (local.get 0) (local.get 1))))
+        "#,
+    );
+    let module = wasmtime::Module::new(
+        store.engine(),
+        r#"
+        (module
+            (import "native" "run_pointer_filter" (func $run (result i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 16) "\07")
+            (func (export "run") (result i32)
+                (call $run)))
+        "#,
+    )
+    .unwrap();
+    let second = linker.instantiate(&mut store, &module).unwrap();
+
+    let set = first
+        .get_typed_func::<(i32, i32), ()>(&mut store, "set")
+        .unwrap();
+    let run = second.get_typed_func::<(), i32>(&mut store, "run").unwrap();
+    set.call(&mut store, (1, 16)).unwrap();
+    // The pointer into the first instance's memory is translated against that memory, even
+    // though the callback runs during a call from the second instance.
+    assert_eq!(run.call(&mut store, ()).unwrap(), 42);
+}
@@ -0,0 +1,63 @@
+//! Helpers shared by the integration tests; not every test uses all of them.
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+use libloading::Library;
+use wasmtime::{Engine, Instance, Linker, Memory, Module, Store};
+use wasmtime_dl::{bind_with_options, BindOptions, ParamType};
+
+/// A function from the test library, imported from `native` under its symbol name, with its
+/// parameter and return types.
+pub type Function<'a> = (&'a str, Vec<ParamType>, Option<ParamType>);
+
+/// The path of the test library built from `tests/native/echo.c`.
+pub fn library_path() -> &'static str {
+    wasmtime_dl_test_native::LIBRARY
+}
+
+/// Loads the test library.
+pub fn library() -> Arc<Library> {
+    Arc::new(unsafe { Library::new(library_path()).unwrap() })
+}
+
+/// A linker with `functions` bound from the test library with `options`.
+pub fn linker(engine: &Engine, functions: &[Function], options: &BindOptions) -> Linker<()> {
+    let mut linker = Linker::new(engine);
+    let library = library();
+    for (name, params, returns) in functions {
+        unsafe {
+            bind_with_options(
+                &mut linker,
+                "native",
+                name,
+                &library,
+                name.as_bytes(),
+                params,
+                returns.clone(),
+                options,
+            )
+            .unwrap();
+        }
+    }
+    linker
+}
+
+/// Instantiates the WAT `module` in a new store.
+pub fn instantiate_with(linker: &Linker<()>, module: &str) -> (Store<()>, Instance) {
+    let mut store = Store::new(linker.engine(), ());
+    let module = Module::new(linker.engine(), module).unwrap();
+    let instance = linker.instantiate(&mut store, &module).unwrap();
+    (store, instance)
+}
+
+/// Instantiates the WAT `module` with `functions` bound from the test library.
+pub fn instantiate(module: &str, functions: &[Function]) -> (Store<()>, Instance) {
+    let engine = Engine::default();
+    instantiate_with(&linker(&engine, functions, &BindOptions::default()), module)
+}
+
+/// The memory `instance` exports as `memory`.
+pub fn memory(store: &mut Store<()>, instance: &Instance) -> Memory {
+    instance.get_memory(store, "memory").unwrap()
+}
@@ -0,0 +1,97 @@
+use wasmtime::{Engine, Linker};
+use wasmtime_dl::{bind, Error, ParamType};
+
+mod common;
+
+#[test]
+fn reports_missing_symbols() {
+    let engine = Engine::default();
+    let mut linker = Linker::<()>::new(&engine);
+
+    let result = unsafe {
+        bind(
+            &mut linker,
+            "native",
+            "missing",
+            &common::library(),
+            b"does_not_exist",
+            &[],
+            None,
+        )
+    };
+    assert!(matches!(
+        result,
+        Err(Error::SymbolNotFound { symbol, .. }) if symbol == "does_not_exist"
+    ));
+}
+
+#[test]
+fn reports_duplicate_definitions() {
+    let engine = Engine::default();
+    let mut linker = Linker::<()>::new(&engine);
+    let library = common::library();
+    let bind_echo = |linker: &mut Linker<()>| unsafe {
+        bind(
+            linker,
+            "native",
+            "echo_f32",
+            &library,
+            b"echo_f32",
+            &[ParamType::F32],
+            Some(ParamType::F32),
+        )
+    };
+
+    bind_echo(&mut linker).unwrap();
+    assert!(matches!(
+        bind_echo(&mut linker),
+        Err(Error::DuplicateDefinition { module, name })
+            if module == "native" && name == "echo_f32"
+    ));
+}
+
+#[test]
+fn rejects_too_many_parameters() {
+    let engine = Engine::default();
+    let mut linker = Linker::<()>::new(&engine);
+
+    let result = unsafe {
+        bind(
+            &mut linker,
+            "native",
+            "sum_many",
+            &common::library(),
+            b"sum_many",
+            &vec![ParamType::I32; wasmtime_dl::MAX_PARAMS + 1],
+            None,
+        )
+    };
+    assert!(matches!(
+        result,
+        Err(Error::UnsupportedSignature { module, name, params, returns })
+            if module == "native" && name == "sum_many"
+                && params.len() == wasmtime_dl::MAX_PARAMS + 1 && returns.is_none()
+    ));
+}
+
+#[test]
+fn counts_the_struct_return_pointer_as_a_parameter() {
+    let engine = Engine::default();
+    let mut linker = Linker::<()>::new(&engine);
+
+    let result = unsafe {
+        bind(
+            &mut linker,
+            "native",
+            "sum_many",
+            &common::library(),
+            b"sum_many",
+            &vec![ParamType::I32; wasmtime_dl::MAX_PARAMS],
+            Some(ParamType::Struct(vec![ParamType::I32])),
+        )
+    };
+    assert!(matches!(
+        result,
+        Err(Error::UnsupportedSignature { params, .. }) if params.len() == wasmtime_dl::MAX_PARAMS
+    ));
+}
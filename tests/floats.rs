@@ -0,0 +1,69 @@
+use wasmtime_dl::ParamType;
+
+mod common;
+
+use common::Function;
+
+const MODULE: &str = r#"
+    (module
+        (import "native" "echo_f32" (func $echo_f32 (param f32) (result f32)))
+        (import "native" "echo_f64" (func $echo_f64 (param f64) (result f64)))
+        (import "native" "sum_mixed"
+            (func $sum_mixed (param i32 f32 i64 f64 f32) (result f64)))
+        (func (export "echo_f32") (param f32) (result f32)
+            (call $echo_f32 (local.get 0)))
+        (func (export "echo_f64") (param f64) (result f64)
+            (call $echo_f64 (local.get 0)))
+        (func (export "sum_mixed") (param i32 f32 i64 f64 f32) (result f64)
+            (call $sum_mixed
+                (local.get 0) (local.get 1) (local.get 2) (local.get 3) (local.get 4))))
+"#;
+
+fn functions() -> Vec<Function<'static>> {
+    vec![
+        ("echo_f32", vec![ParamType::F32], Some(ParamType::F32)),
+        ("echo_f64", vec![ParamType::F64], Some(ParamType::F64)),
+        (
+            "sum_mixed",
+            vec![
+                ParamType::I32,
+                ParamType::F32,
+                ParamType::I64,
+                ParamType::F64,
+                ParamType::F32,
+            ],
+            Some(ParamType::F64),
+        ),
+    ]
+}
+
+#[test]
+fn echoes_floats() {
+    let (mut store, instance) = common::instantiate(MODULE, &functions());
+    let echo_f32 = instance
+        .get_typed_func::<f32, f32>(&mut store, "echo_f32")
+        .unwrap();
+    let echo_f64 = instance
+        .get_typed_func::<f64, f64>(&mut store, "echo_f64")
+        .unwrap();
+
+    for x in [0.0, -1.5, 3.25, f32::MAX, f32::MIN_POSITIVE] {
+        assert_eq!(echo_f32.call(&mut store, x).unwrap(), x);
+    }
+    for x in [0.0, -1.5, 3.25, f64::MAX, f64::MIN_POSITIVE] {
+        assert_eq!(echo_f64.call(&mut store, x).unwrap(), x);
+    }
+}
+
+#[test]
+fn mixes_integer_and_float_registers() {
+    let (mut store, instance) = common::instantiate(MODULE, &functions());
+    let sum_mixed = instance
+        .get_typed_func::<(i32, f32, i64, f64, f32), f64>(&mut store, "sum_mixed")
+        .unwrap();
+
+    let sum = sum_mixed
+        .call(&mut store, (1, 0.5, 1 << 40, 0.25, 2.0))
+        .unwrap();
+    assert_eq!(sum, (1u64 << 40) as f64 + 3.75);
+}
@@ -0,0 +1,140 @@
+use wasmtime::{Engine, Linker, Store, TypedFunc};
+use wasmtime_dl::{BindOptions, HandleTable, ParamType};
+
+mod common;
+
+const MODULE: &str = r#"
+    (module
+        (import "native" "counter_get" (func $get (param i32) (result i32)))
+        (import "native" "counter_increment" (func $increment (param i32) (result i32)))
+        (import "native" "counter_reset" (func $reset (param i32)))
+        (export "counter_get" (func $get))
+        (export "counter_increment" (func $increment))
+        (export "counter_reset" (func $reset)))
+"#;
+
+struct Counters {
+    store: Store<()>,
+    get: TypedFunc<i32, i32>,
+    increment: TypedFunc<i32, i32>,
+    reset: TypedFunc<i32, ()>,
+}
+
+fn linker() -> Linker<()> {
+    common::linker(
+        &Engine::default(),
+        &[
+            ("counter_get", vec![ParamType::I32], Some(ParamType::Handle)),
+            (
+                "counter_increment",
+                vec![ParamType::Handle],
+                Some(ParamType::I32),
+            ),
+            ("counter_reset", vec![ParamType::ConsumedHandle], None),
+        ],
+        &BindOptions::default(),
+    )
+}
+
+fn instantiate(linker: &Linker<()>) -> Counters {
+    let (mut store, instance) = common::instantiate_with(linker, MODULE);
+    Counters {
+        get: instance.get_typed_func(&mut store, "counter_get").unwrap(),
+        increment: instance
+            .get_typed_func(&mut store, "counter_increment")
+            .unwrap(),
+        reset: instance
+            .get_typed_func(&mut store, "counter_reset")
+            .unwrap(),
+        store,
+    }
+}
+
+#[test]
+fn passes_returned_pointers_back_as_handles() {
+    let Counters {
+        mut store,
+        get,
+        increment,
+        ..
+    } = instantiate(&linker());
+
+    let counter = get.call(&mut store, 0).unwrap();
+    assert_eq!(counter, 1);
+    assert_eq!(get.call(&mut store, 0).unwrap(), counter);
+    assert_eq!(increment.call(&mut store, counter).unwrap(), 1);
+    assert_eq!(increment.call(&mut store, counter).unwrap(), 2);
+
+    assert_eq!(get.call(&mut store, 2).unwrap(), 0);
+    assert_eq!(increment.call(&mut store, 0).unwrap(), -1);
+}
+
+#[test]
+fn traps_on_unknown_handles() {
+    let Counters {
+        mut store,
+        get,
+        increment,
+        ..
+    } = instantiate(&linker());
+
+    let counter = get.call(&mut store, 1).unwrap();
+    for forged in [counter + 1, -1] {
+        assert!(increment.call(&mut store, forged).is_err());
+    }
+    assert!(HandleTable::of(&mut store).remove(counter as u32).is_some());
+    assert!(increment.call(&mut store, counter).is_err());
+    assert_ne!(get.call(&mut store, 1).unwrap(), counter);
+}
+
+#[test]
+fn releases_consumed_handles() {
+    let Counters {
+        mut store,
+        get,
+        increment,
+        reset,
+    } = instantiate(&linker());
+
+    let counter = get.call(&mut store, 1).unwrap();
+    increment.call(&mut store, counter).unwrap();
+    reset.call(&mut store, counter).unwrap();
+    assert!(increment.call(&mut store, counter).is_err());
+    assert!(reset.call(&mut store, counter).is_err());
+
+    let counter = get.call(&mut store, 1).unwrap();
+    assert_eq!(increment.call(&mut store, counter).unwrap(), 1);
+}
+
+#[test]
+fn keeps_handles_to_their_store() {
+    let linker = linker();
+    let mut first = instantiate(&linker);
+    let mut second = instantiate(&linker);
+
+    let counter = first.get.call(&mut first.store, 0).unwrap();
+    assert!(second.increment.call(&mut second.store, counter).is_err());
+    assert!(HandleTable::of(&mut second.store)
+        .get(counter as u32)
+        .is_none());
+}
+
+#[test]
+fn reuses_removed_handles_without_resolving_stale_ones() {
+    let mut store = Store::new(&Engine::default(), ());
+    let handles = HandleTable::of(&mut store);
+
+    let first = handles.insert(8).unwrap();
+    assert_eq!(handles.remove(first), Some(8));
+    let second = handles.insert(16).unwrap();
+    assert_ne!(first, second);
+    assert_eq!(handles.get(first), None);
+    assert_eq!(handles.remove(first), None);
+    assert_eq!(handles.get(second), Some(16));
+
+    // More handles than fit in the table at once, since each one's slot is freed again.
+    for ptr in 1..=1 << 20 {
+        let handle = handles.insert(ptr * 8).unwrap();
+        assert_eq!(handles.remove(handle), Some(ptr * 8));
+    }
+}
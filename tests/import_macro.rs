@@ -0,0 +1,129 @@
+use wasmtime::{Config, Engine, Instance, Linker, Store, Trap};
+
+mod common;
+
+mod native {
+    #[wasmtime_dl::import(lib = "echo", module = "native")]
+    extern "C" {
+        fn echo_f64(x: f64) -> f64;
+        fn sum_mixed(a: i32, b: f32, c: i64, d: f64, e: f32) -> f64;
+        #[link_name = "sum_bytes"]
+        fn sum(bytes: *const u8, len: i32) -> i32;
+        fn find_byte(bytes: *const u8, len: i32, byte: i32) -> *const u8;
+        fn counter_get(index: i32) -> *mut u8;
+    }
+}
+
+mod native64 {
+    #[wasmtime_dl::import(lib = "echo", module = "native", memory64 = true)]
+    extern "C" {
+        fn find_byte(bytes: *const u8, len: i32, byte: i32) -> *const u8;
+        fn size_to_u64(x: usize) -> u64;
+    }
+}
+
+const MODULE: &str = r#"
+    (module
+        (import "native" "echo_f64" (func $echo_f64 (param f64) (result f64)))
+        (import "native" "sum_mixed"
+            (func $sum_mixed (param i32 f32 i64 f64 f32) (result f64)))
+        (import "native" "sum" (func $sum (param i32 i32) (result i32)))
+        (import "native" "find_byte" (func $find_byte (param i32 i32 i32) (result i32)))
+        (import "native" "counter_get" (func $counter_get (param i32) (result i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 8) "\01\02\03\04")
+        (func (export "echo_f64") (param f64) (result f64)
+            (call $echo_f64 (local.get 0)))
+        (func (export "sum_mixed") (param i32 f32 i64 f64 f32) (result f64)
+            (call $sum_mixed
+                (local.get 0) (local.get 1) (local.get 2) (local.get 3) (local.get 4)))
+        (func (export "sum") (param i32 i32) (result i32)
+            (call $sum (local.get 0) (local.get 1)))
+        (func (export "find_byte") (param i32 i32 i32) (result i32)
+            (call $find_byte (local.get 0) (local.get 1) (local.get 2)))
+        (func (export "counter_get") (param i32) (result i32)
+            (call $counter_get (local.get 0))))
+"#;
+
+fn instantiate() -> (Store<()>, Instance) {
+    let mut linker = Linker::new(&Engine::default());
+    unsafe { native::add_to_linker_with_library(&mut linker, &common::library()).unwrap() };
+    common::instantiate_with(&linker, MODULE)
+}
+
+#[test]
+fn calls_typed_bindings() {
+    let (mut store, instance) = instantiate();
+    let echo_f64 = instance
+        .get_typed_func::<f64, f64>(&mut store, "echo_f64")
+        .unwrap();
+    let sum_mixed = instance
+        .get_typed_func::<(i32, f32, i64, f64, f32), f64>(&mut store, "sum_mixed")
+        .unwrap();
+    let sum = instance
+        .get_typed_func::<(i32, i32), i32>(&mut store, "sum")
+        .unwrap();
+
+    assert_eq!(echo_f64.call(&mut store, -1.5).unwrap(), -1.5);
+    assert_eq!(
+        sum_mixed
+            .call(&mut store, (1, 0.5, 1 << 40, 0.25, 2.0))
+            .unwrap(),
+        (1u64 << 40) as f64 + 3.75
+    );
+    assert_eq!(sum.call(&mut store, (8, 4)).unwrap(), 10);
+}
+
+#[test]
+fn translates_returned_pointers() {
+    let (mut store, instance) = instantiate();
+    let find_byte = instance
+        .get_typed_func::<(i32, i32, i32), i32>(&mut store, "find_byte")
+        .unwrap();
+    let counter_get = instance
+        .get_typed_func::<i32, i32>(&mut store, "counter_get")
+        .unwrap();
+
+    assert_eq!(find_byte.call(&mut store, (8, 4, 3)).unwrap(), 10);
+    assert_eq!(find_byte.call(&mut store, (8, 4, 5)).unwrap(), 0);
+    let err = counter_get.call(&mut store, 0).unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Trap::MemoryOutOfBounds));
+}
+
+#[test]
+fn passes_64_bit_offsets_and_sizes() {
+    let engine = Engine::new(Config::new().wasm_memory64(true)).unwrap();
+    let mut linker = Linker::new(&engine);
+    unsafe { native64::add_to_linker_with_library(&mut linker, &common::library()).unwrap() };
+    let (mut store, instance) = common::instantiate_with(
+        &linker,
+        r#"
+        (module
+            (import "native" "find_byte" (func $find_byte (param i64 i32 i32) (result i64)))
+            (import "native" "size_to_u64" (func $size_to_u64 (param i64) (result i64)))
+            (memory (export "memory") i64 1)
+            (data (i64.const 8) "\01\02\03\04")
+            (func (export "find_byte") (param i64 i32 i32) (result i64)
+                (call $find_byte (local.get 0) (local.get 1) (local.get 2)))
+            (func (export "size_to_u64") (param i64) (result i64)
+                (call $size_to_u64 (local.get 0))))
+        "#,
+    );
+    let find_byte = instance
+        .get_typed_func::<(i64, i32, i32), i64>(&mut store, "find_byte")
+        .unwrap();
+    let size_to_u64 = instance
+        .get_typed_func::<i64, i64>(&mut store, "size_to_u64")
+        .unwrap();
+
+    assert_eq!(find_byte.call(&mut store, (8, 4, 3)).unwrap(), 10);
+    assert_eq!(size_to_u64.call(&mut store, 1 << 40).unwrap(), 1 << 40);
+}
+
+#[test]
+fn reports_missing_libraries() {
+    let engine = Engine::default();
+    let mut linker = Linker::<()>::new(&engine);
+    let err = unsafe { native::add_to_linker(&mut linker) }.unwrap_err();
+    assert!(matches!(err, wasmtime_dl::Error::LibraryLoadError { .. }));
+}
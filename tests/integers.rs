@@ -0,0 +1,96 @@
+use wasmtime_dl::ParamType;
+
+mod common;
+
+use common::Function;
+
+const MODULE: &str = r#"
+    (module
+        (import "native" "add_u8" (func $add_u8 (param i32 i32) (result i32)))
+        (import "native" "negate_i16" (func $negate_i16 (param i32) (result i32)))
+        (import "native" "is_negative" (func $is_negative (param i32) (result i32)))
+        (import "native" "size_to_u64" (func $size_to_u64 (param i32) (result i64)))
+        (import "native" "ptrdiff_to_i64" (func $ptrdiff_to_i64 (param i32) (result i64)))
+        (import "native" "color_pack" (func $color_pack (param i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 8) "\01\02\03\04")
+        (export "add_u8" (func $add_u8))
+        (export "negate_i16" (func $negate_i16))
+        (export "is_negative" (func $is_negative))
+        (export "size_to_u64" (func $size_to_u64))
+        (export "ptrdiff_to_i64" (func $ptrdiff_to_i64))
+        (func (export "color_pack") (param i32) (result i32)
+            (call $color_pack (i32.const 8) (local.get 0))))
+"#;
+
+fn functions() -> Vec<Function<'static>> {
+    let color = ParamType::Struct(vec![ParamType::U8; 4]);
+    vec![
+        (
+            "add_u8",
+            vec![ParamType::U8, ParamType::U8],
+            Some(ParamType::U8),
+        ),
+        ("negate_i16", vec![ParamType::I16], Some(ParamType::I16)),
+        ("is_negative", vec![ParamType::I8], Some(ParamType::Bool)),
+        ("size_to_u64", vec![ParamType::Usize], Some(ParamType::U64)),
+        (
+            "ptrdiff_to_i64",
+            vec![ParamType::Isize],
+            Some(ParamType::I64),
+        ),
+        (
+            "color_pack",
+            vec![color, ParamType::Bool],
+            Some(ParamType::U32),
+        ),
+    ]
+}
+
+#[test]
+fn truncates_and_extends_small_integers() {
+    let (mut store, instance) = common::instantiate(MODULE, &functions());
+    let add_u8 = instance
+        .get_typed_func::<(i32, i32), i32>(&mut store, "add_u8")
+        .unwrap();
+    let negate_i16 = instance
+        .get_typed_func::<i32, i32>(&mut store, "negate_i16")
+        .unwrap();
+    let is_negative = instance
+        .get_typed_func::<i32, i32>(&mut store, "is_negative")
+        .unwrap();
+
+    // Arguments are truncated to the native type, and results wrap within it.
+    assert_eq!(add_u8.call(&mut store, (200, 100)).unwrap(), 44);
+    assert_eq!(add_u8.call(&mut store, (0x101, 1)).unwrap(), 2);
+    assert_eq!(negate_i16.call(&mut store, 5).unwrap(), -5);
+    assert_eq!(negate_i16.call(&mut store, -32768).unwrap(), -32768);
+    assert_eq!(is_negative.call(&mut store, 0xff).unwrap(), 1);
+    assert_eq!(is_negative.call(&mut store, 0x7f).unwrap(), 0);
+}
+
+#[test]
+fn extends_sizes_by_signedness() {
+    let (mut store, instance) = common::instantiate(MODULE, &functions());
+    let size_to_u64 = instance
+        .get_typed_func::<i32, i64>(&mut store, "size_to_u64")
+        .unwrap();
+    let ptrdiff_to_i64 = instance
+        .get_typed_func::<i32, i64>(&mut store, "ptrdiff_to_i64")
+        .unwrap();
+
+    assert_eq!(size_to_u64.call(&mut store, -1).unwrap(), 0xffff_ffff);
+    assert_eq!(ptrdiff_to_i64.call(&mut store, -1).unwrap(), -1);
+}
+
+#[test]
+fn lays_out_small_integer_struct_fields() {
+    let (mut store, instance) = common::instantiate(MODULE, &functions());
+    let color_pack = instance
+        .get_typed_func::<i32, i32>(&mut store, "color_pack")
+        .unwrap();
+
+    assert_eq!(color_pack.call(&mut store, 0).unwrap(), 0x0102_0304);
+    // Any non-zero value is true.
+    assert_eq!(color_pack.call(&mut store, 2).unwrap(), 0x0102_03ff);
+}
@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use wasmtime::{Engine, Linker, Module, Store};
+use wasmtime_dl::{bind, ParamType};
+
+mod common;
+
+#[test]
+fn library_lives_as_long_as_its_bindings() {
+    let library = common::library();
+    let engine = Engine::default();
+    let mut linker = Linker::<()>::new(&engine);
+    unsafe {
+        bind(
+            &mut linker,
+            "native",
+            "echo_f32",
+            &library,
+            b"echo_f32",
+            &[ParamType::F32],
+            Some(ParamType::F32),
+        )
+        .unwrap()
+    };
+    assert!(Arc::strong_count(&library) > 1);
+
+    let module = Module::new(
+        &engine,
+        r#"(module (import "native" "echo_f32" (func (param f32) (result f32))))"#,
+    )
+    .unwrap();
+    let mut store = Store::new(&engine, ());
+    linker.instantiate(&mut store, &module).unwrap();
+    drop(linker);
+    assert!(Arc::strong_count(&library) > 1);
+
+    drop(store);
+    assert_eq!(Arc::strong_count(&library), 1);
+}
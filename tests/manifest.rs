@@ -0,0 +1,107 @@
+use std::fs;
+
+use wasmtime::{Engine, Linker, Module, Store};
+use wasmtime_dl::{Error, Manifest, ParamType};
+
+mod common;
+
+const MODULE: &str = r#"
+    (module
+        (import "native" "echo" (func $echo (param f32) (result f32)))
+        (import "native" "sum_bytes" (func $sum_bytes (param i32 i32) (result i32)))
+        (memory (export "heap") 1)
+        (data (i32.const 4) "\01\02\03")
+        (func (export "echo") (param f32) (result f32)
+            (call $echo (local.get 0)))
+        (func (export "sum_bytes") (result i32)
+            (call $sum_bytes (i32.const 4) (i32.const 3))))
+"#;
+
+fn toml_manifest() -> String {
+    format!(
+        r#"
+        library = {:?}
+        module = "native"
+        memory = "heap"
+
+        [[functions]]
+        name = "echo"
+        symbol = "echo_f32"
+        params = ["f32"]
+        returns = "f32"
+
+        [[functions]]
+        name = "sum_bytes"
+        params = [{{ buffer = 3 }}, "i32"]
+        returns = "i32"
+        "#,
+        common::library_path()
+    )
+}
+
+fn run(manifest: &Manifest) {
+    let engine = Engine::default();
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::new(&engine);
+    unsafe { manifest.apply(&mut linker).unwrap() };
+
+    let module = Module::new(&engine, MODULE).unwrap();
+    let instance = linker.instantiate(&mut store, &module).unwrap();
+    let echo = instance
+        .get_typed_func::<f32, f32>(&mut store, "echo")
+        .unwrap();
+    let sum_bytes = instance
+        .get_typed_func::<(), i32>(&mut store, "sum_bytes")
+        .unwrap();
+    assert_eq!(echo.call(&mut store, 1.25).unwrap(), 1.25);
+    assert_eq!(sum_bytes.call(&mut store, ()).unwrap(), 6);
+}
+
+#[test]
+fn parses_toml() {
+    let manifest = Manifest::from_toml(&toml_manifest()).unwrap();
+
+    assert_eq!(manifest.functions.len(), 2);
+    assert_eq!(manifest.functions[0].symbol.as_deref(), Some("echo_f32"));
+    assert_eq!(
+        manifest.functions[1].params,
+        [ParamType::Buffer(3), ParamType::I32]
+    );
+    run(&manifest);
+}
+
+#[test]
+fn loads_json_files() {
+    let dir = std::env::temp_dir().join(format!("wasmtime-dl-manifest-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("bindings.json");
+    let json = serde_json::json!({
+        "library": common::library_path(),
+        "module": "native",
+        "memory": "heap",
+        "functions": [
+            { "name": "echo", "symbol": "echo_f32", "params": ["f32"], "returns": "f32" },
+            { "name": "sum_bytes", "params": ["pointer", "i32"], "returns": "i32" },
+        ],
+    });
+    fs::write(&path, json.to_string()).unwrap();
+
+    let manifest = Manifest::load(&path).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    run(&manifest);
+}
+
+#[test]
+fn rejects_unknown_types() {
+    let result = Manifest::from_toml(
+        r#"
+        library = "libfoo.so"
+        module = "native"
+
+        [[functions]]
+        name = "foo"
+        params = ["u128"]
+        "#,
+    );
+    assert!(matches!(result, Err(Error::ManifestTomlError(_))));
+}
@@ -0,0 +1,222 @@
+use wasmtime::{Config, Engine, Linker, Memory, MemoryType, Module, Store, Trap};
+use wasmtime_dl::{bind_with_options, BindOptions, GuestMemory, ParamType};
+
+mod common;
+
+fn bind_sum_bytes(linker: &mut Linker<()>, memory: GuestMemory) {
+    unsafe {
+        bind_with_options(
+            linker,
+            "native",
+            "sum_bytes",
+            &common::library(),
+            b"sum_bytes",
+            &[ParamType::Pointer, ParamType::I32],
+            Some(ParamType::I32),
+            &BindOptions {
+                memory,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    };
+}
+
+#[test]
+fn uses_a_named_memory_export() {
+    let engine = Engine::default();
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::new(&engine);
+    bind_sum_bytes(&mut linker, GuestMemory::Export("heap".to_owned()));
+
+    let module = Module::new(
+        &engine,
+        r#"
+        (module
+            (import "native" "sum_bytes" (func $sum_bytes (param i32 i32) (result i32)))
+            (memory (export "heap") 1)
+            (data (i32.const 0) "\05\06")
+            (func (export "run") (result i32)
+                (call $sum_bytes (i32.const 0) (i32.const 2))))
+        "#,
+    )
+    .unwrap();
+    let instance = linker.instantiate(&mut store, &module).unwrap();
+    let run = instance
+        .get_typed_func::<(), i32>(&mut store, "run")
+        .unwrap();
+    assert_eq!(run.call(&mut store, ()).unwrap(), 11);
+}
+
+#[test]
+fn uses_a_host_provided_memory() {
+    let engine = Engine::default();
+    let mut store = Store::new(&engine, ());
+    let memory = Memory::new(&mut store, MemoryType::new(1, None)).unwrap();
+    memory.write(&mut store, 16, &[7, 8, 9]).unwrap();
+    let mut linker = Linker::new(&engine);
+    linker.define(&store, "env", "memory", memory).unwrap();
+    bind_sum_bytes(&mut linker, GuestMemory::Host(memory));
+
+    let module = Module::new(
+        &engine,
+        r#"
+        (module
+            (import "native" "sum_bytes" (func $sum_bytes (param i32 i32) (result i32)))
+            (import "env" "memory" (memory 1))
+            (func (export "run") (result i32)
+                (call $sum_bytes (i32.const 16) (i32.const 3))))
+        "#,
+    )
+    .unwrap();
+    let instance = linker.instantiate(&mut store, &module).unwrap();
+    let run = instance
+        .get_typed_func::<(), i32>(&mut store, "run")
+        .unwrap();
+    assert_eq!(run.call(&mut store, ()).unwrap(), 24);
+}
+
+#[test]
+fn traps_when_the_memory_is_missing() {
+    let engine = Engine::default();
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::new(&engine);
+    bind_sum_bytes(&mut linker, GuestMemory::default());
+
+    let module = Module::new(
+        &engine,
+        r#"
+        (module
+            (import "native" "sum_bytes" (func $sum_bytes (param i32 i32) (result i32)))
+            (memory (export "heap") 1)
+            (func (export "run") (result i32)
+                (call $sum_bytes (i32.const 0) (i32.const 0))))
+        "#,
+    )
+    .unwrap();
+    let instance = linker.instantiate(&mut store, &module).unwrap();
+    let run = instance
+        .get_typed_func::<(), i32>(&mut store, "run")
+        .unwrap();
+    let err = run.call(&mut store, ()).unwrap_err();
+    assert!(
+        format!("{err:?}").contains("does not export a memory named `memory`"),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn binds_each_function_to_its_own_memory() {
+    let engine = Engine::default();
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::new(&engine);
+    let library = common::library();
+    for (name, memory) in [("sum_first", "first"), ("sum_second", "second")] {
+        unsafe {
+            bind_with_options(
+                &mut linker,
+                "native",
+                name,
+                &library,
+                b"sum_bytes",
+                &[ParamType::Pointer, ParamType::I32],
+                Some(ParamType::I32),
+                &BindOptions {
+                    memory: GuestMemory::Export(memory.to_owned()),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+        };
+    }
+
+    let module = Module::new(
+        &engine,
+        r#"
+        (module
+            (import "native" "sum_first" (func $sum_first (param i32 i32) (result i32)))
+            (import "native" "sum_second" (func $sum_second (param i32 i32) (result i32)))
+            (memory $first (export "first") 1)
+            (memory $second (export "second") 1)
+            (data (memory $first) (i32.const 0) "\01\02")
+            (data (memory $second) (i32.const 0) "\0a\14")
+            (func (export "run") (result i32)
+                (i32.sub
+                    (call $sum_second (i32.const 0) (i32.const 2))
+                    (call $sum_first (i32.const 0) (i32.const 2)))))
+        "#,
+    )
+    .unwrap();
+    let instance = linker.instantiate(&mut store, &module).unwrap();
+    let run = instance
+        .get_typed_func::<(), i32>(&mut store, "run")
+        .unwrap();
+    assert_eq!(run.call(&mut store, ()).unwrap(), 27);
+}
+
+#[test]
+fn passes_64_bit_offsets_into_memory64_memories() {
+    let engine = Engine::new(Config::new().wasm_memory64(true)).unwrap();
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::new(&engine);
+    let options = BindOptions {
+        memory64: true,
+        ..Default::default()
+    };
+    let library = common::library();
+    unsafe {
+        bind_with_options(
+            &mut linker,
+            "native",
+            "sum_bytes",
+            &library,
+            b"sum_bytes",
+            &[ParamType::Pointer, ParamType::I32],
+            Some(ParamType::I32),
+            &options,
+        )
+        .unwrap();
+        bind_with_options(
+            &mut linker,
+            "native",
+            "str_len",
+            &library,
+            b"str_len",
+            &[ParamType::Str],
+            Some(ParamType::I32),
+            &options,
+        )
+        .unwrap();
+    }
+
+    let module = Module::new(
+        &engine,
+        r#"
+        (module
+            (import "native" "sum_bytes" (func $sum_bytes (param i64 i32) (result i32)))
+            (import "native" "str_len" (func $str_len (param i64 i64) (result i32)))
+            (memory (export "memory") i64 1)
+            (data (i64.const 8) "\03\04hello")
+            (func (export "sum_bytes") (param i64) (result i32)
+                (call $sum_bytes (local.get 0) (i32.const 2)))
+            (func (export "str_len") (param i64) (result i32)
+                (call $str_len (i64.const 10) (local.get 0))))
+        "#,
+    )
+    .unwrap();
+    let instance = linker.instantiate(&mut store, &module).unwrap();
+    let sum_bytes = instance
+        .get_typed_func::<i64, i32>(&mut store, "sum_bytes")
+        .unwrap();
+    let str_len = instance
+        .get_typed_func::<i64, i32>(&mut store, "str_len")
+        .unwrap();
+
+    assert_eq!(sum_bytes.call(&mut store, 8).unwrap(), 7);
+    assert_eq!(str_len.call(&mut store, 5).unwrap(), 5);
+    // Offsets beyond 32 bits are bounds checked rather than truncated.
+    let err = sum_bytes.call(&mut store, 1 << 32).unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Trap::MemoryOutOfBounds));
+    let err = str_len.call(&mut store, -1).unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Trap::MemoryOutOfBounds));
+}
@@ -0,0 +1,29 @@
+use std::env::{self, consts};
+use std::path::PathBuf;
+
+/// Builds `echo.c` as a shared library and exposes its path through `WASMTIME_DL_TEST_LIB`.
+fn main() {
+    let source = "echo.c";
+    println!("cargo:rerun-if-changed={source}");
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let lib = out_dir.join(format!("{}echo{}", consts::DLL_PREFIX, consts::DLL_SUFFIX));
+
+    let compiler = cc::Build::new().get_compiler();
+    let mut cmd = compiler.to_command();
+    if compiler.is_like_msvc() {
+        cmd.arg("/LD")
+            .arg(source)
+            .arg(format!("/Fo{}\\", out_dir.display()))
+            .arg(format!("/Fe{}", lib.display()));
+    } else {
+        cmd.arg("-shared")
+            .arg("-fPIC")
+            .arg(source)
+            .arg("-o")
+            .arg(&lib);
+    }
+    let status = cmd.status().expect("failed to run the C compiler");
+    assert!(status.success(), "building {source} failed ({status})");
+    println!("cargo:rustc-env=WASMTIME_DL_TEST_LIB={}", lib.display());
+}
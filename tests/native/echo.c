@@ -0,0 +1,218 @@
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+float echo_f32(float x) {
+    return x;
+}
+
+double echo_f64(double x) {
+    return x;
+}
+
+double sum_mixed(int32_t a, float b, int64_t c, double d, float e) {
+    return (double)a + b + c + d + e;
+}
+
+int32_t sum_bytes(const uint8_t* bytes, int32_t len) {
+    int32_t sum = 0;
+    for (int32_t i = 0; i < len; i++) {
+        sum += bytes[i];
+    }
+    return sum;
+}
+
+int32_t read_i32(const int32_t* ptr) {
+    return *ptr;
+}
+
+const uint8_t* find_byte(const uint8_t* bytes, int32_t len, int32_t byte) {
+    for (int32_t i = 0; i < len; i++) {
+        if (bytes[i] == byte) {
+            return &bytes[i];
+        }
+    }
+    return NULL;
+}
+
+double sum_many(int32_t a1, float a2, int64_t a3, double a4, int32_t a5, float a6, int64_t a7,
+                double a8, int32_t a9, float a10, int64_t a11, double a12, int32_t a13, float a14,
+                int64_t a15, double a16, int32_t a17, double a18) {
+    return (double)a1 + a2 + a3 + a4 + a5 + a6 + a7 + a8 + a9 + a10 + a11 + a12 + a13 + a14 + a15 +
+           a16 + a17 + a18;
+}
+
+int32_t str_len(const char* str) {
+    int32_t len = 0;
+    while (str[len] != '\0') {
+        len++;
+    }
+    return len;
+}
+
+int32_t cstr_len(const char* str) {
+    return str_len(str);
+}
+
+const char* greeting(int32_t hello) {
+    return hello ? "hello" : 0;
+}
+
+typedef struct Counter {
+    int32_t count;
+} Counter;
+
+static Counter counters[2];
+
+Counter* counter_get(int32_t index) {
+    return index >= 0 && index < 2 ? &counters[index] : 0;
+}
+
+int32_t counter_increment(Counter* counter) {
+    return counter ? ++counter->count : -1;
+}
+
+void counter_reset(Counter* counter) {
+    counter->count = 0;
+}
+
+typedef struct Point {
+    float x, y;
+} Point;
+
+typedef struct Span {
+    int32_t start;
+    int64_t len;
+} Span;
+
+typedef struct Rect {
+    Point origin;
+    double scale;
+    int32_t id;
+} Rect;
+
+Point point_scale(Point p, float factor) {
+    Point scaled = {p.x * factor, p.y * factor};
+    return scaled;
+}
+
+int64_t span_end(Span span) {
+    return span.start + span.len;
+}
+
+Rect rect_make(float x, float y, double scale, int32_t id) {
+    Rect rect = {{x, y}, scale, id};
+    return rect;
+}
+
+double rect_sum(Rect rect) {
+    return (double)rect.origin.x + rect.origin.y + rect.scale + rect.id;
+}
+
+void get_size(int32_t* w, int32_t* h) {
+    if (w) {
+        *w = 640;
+    }
+    if (h) {
+        *h = 480;
+    }
+}
+
+void point_translate(Point* p, float dx, float dy) {
+    p->x += dx;
+    p->y += dy;
+}
+
+void sort_i32(int32_t* items, int32_t len, int32_t (*cmp)(const int32_t*, const int32_t*)) {
+    for (int32_t i = 1; i < len; i++) {
+        for (int32_t j = i; j > 0 && cmp(&items[j - 1], &items[j]) > 0; j--) {
+            int32_t item = items[j];
+            items[j] = items[j - 1];
+            items[j - 1] = item;
+        }
+    }
+}
+
+double apply_twice(double (*f)(double, int64_t), double x) {
+    return f(f(x, 1), 2);
+}
+
+typedef int32_t (*Filter)(int32_t);
+
+static Filter filters[4];
+
+bool set_filter(int32_t slot, Filter filter) {
+    bool same = filters[slot] == filter;
+    filters[slot] = filter;
+    return same;
+}
+
+int32_t run_filter(int32_t slot, int32_t value) {
+    return filters[slot] ? filters[slot](value) : -1;
+}
+
+typedef int32_t (*PointerFilter)(const int32_t*);
+
+static PointerFilter pointer_filter;
+static const int32_t* pointer_filter_data;
+
+void set_pointer_filter(PointerFilter filter, const int32_t* data) {
+    pointer_filter = filter;
+    pointer_filter_data = data;
+}
+
+int32_t run_pointer_filter(void) {
+    return pointer_filter(pointer_filter_data);
+}
+
+double sum_variadic(const char* format, ...) {
+    va_list args;
+    va_start(args, format);
+    double sum = 0;
+    for (const char* c = format; *c; c++) {
+        switch (*c) {
+        case 'i':
+            sum += va_arg(args, int32_t);
+            break;
+        case 'l':
+            sum += va_arg(args, int64_t);
+            break;
+        case 'f':
+        case 'd':
+            sum += va_arg(args, double);
+            break;
+        }
+    }
+    va_end(args);
+    return sum;
+}
+
+uint8_t add_u8(uint8_t a, uint8_t b) {
+    return a + b;
+}
+
+int16_t negate_i16(int16_t x) {
+    return -x;
+}
+
+bool is_negative(int8_t x) {
+    return x < 0;
+}
+
+uint64_t size_to_u64(size_t x) {
+    return x;
+}
+
+int64_t ptrdiff_to_i64(ptrdiff_t x) {
+    return x;
+}
+
+typedef struct {
+    uint8_t r, g, b, a;
+} Color;
+
+uint32_t color_pack(Color color, bool opaque) {
+    uint8_t a = opaque ? 255 : color.a;
+    return (uint32_t)color.r << 24 | (uint32_t)color.g << 16 | (uint32_t)color.b << 8 | a;
+}
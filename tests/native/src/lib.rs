@@ -0,0 +1,6 @@
+//! The native library that the integration tests of `wasmtime-dl` bind, built from `echo.c`.
+//!
+//! It lives in its own unpublished crate so that building it stays out of `wasmtime-dl` itself.
+
+/// The path of the built library.
+pub const LIBRARY: &str = env!("WASMTIME_DL_TEST_LIB");
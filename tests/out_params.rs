@@ -0,0 +1,97 @@
+use wasmtime::{Engine, Linker, Trap};
+use wasmtime_dl::{bind, Error, ParamType};
+
+mod common;
+
+use common::Function;
+
+const MODULE: &str = r#"
+    (module
+        (import "native" "get_size" (func $get_size (param i32 i32)))
+        (import "native" "point_translate" (func $point_translate (param i32 f32 f32)))
+        (memory (export "memory") 1)
+        (func (export "get_size") (param i32 i32)
+            (call $get_size (local.get 0) (local.get 1)))
+        (func (export "point_translate") (param i32 f32 f32)
+            (call $point_translate (local.get 0) (local.get 1) (local.get 2))))
+"#;
+
+fn functions() -> Vec<Function<'static>> {
+    let out_i32 = ParamType::Out(Box::new(ParamType::I32));
+    let point = ParamType::Struct(vec![ParamType::F32, ParamType::F32]);
+    vec![
+        ("get_size", vec![out_i32.clone(), out_i32], None),
+        (
+            "point_translate",
+            vec![
+                ParamType::InOut(Box::new(point)),
+                ParamType::F32,
+                ParamType::F32,
+            ],
+            None,
+        ),
+    ]
+}
+
+#[test]
+fn copies_out_parameters_back_after_the_call() {
+    let (mut store, instance) = common::instantiate(MODULE, &functions());
+    let memory = common::memory(&mut store, &instance);
+    let get_size = instance
+        .get_typed_func::<(i32, i32), ()>(&mut store, "get_size")
+        .unwrap();
+
+    get_size.call(&mut store, (8, 12)).unwrap();
+    assert_eq!(memory.data(&store)[8..12], 640i32.to_le_bytes());
+    assert_eq!(memory.data(&store)[12..16], 480i32.to_le_bytes());
+
+    // A zero offset is passed as a null pointer, which native code can skip.
+    get_size.call(&mut store, (16, 0)).unwrap();
+    assert_eq!(memory.data(&store)[16..20], 640i32.to_le_bytes());
+    assert_eq!(memory.data(&store)[..4], [0; 4]);
+}
+
+#[test]
+fn copies_in_out_parameters_both_ways() {
+    let (mut store, instance) = common::instantiate(MODULE, &functions());
+    let memory = common::memory(&mut store, &instance);
+    let point_translate = instance
+        .get_typed_func::<(i32, f32, f32), ()>(&mut store, "point_translate")
+        .unwrap();
+
+    let point = [1f32.to_le_bytes(), 2f32.to_le_bytes()].concat();
+    memory.write(&mut store, 8, &point).unwrap();
+    point_translate.call(&mut store, (8, 0.5, -1.0)).unwrap();
+    let translated = [1.5f32.to_le_bytes(), 1f32.to_le_bytes()].concat();
+    assert_eq!(memory.data(&store)[8..16], translated);
+}
+
+#[test]
+fn traps_on_out_of_bounds_out_parameters() {
+    let (mut store, instance) = common::instantiate(MODULE, &functions());
+    let get_size = instance
+        .get_typed_func::<(i32, i32), ()>(&mut store, "get_size")
+        .unwrap();
+
+    let err = get_size.call(&mut store, (8, 65533)).unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Trap::MemoryOutOfBounds));
+}
+
+#[test]
+fn rejects_out_parameters_of_pointers() {
+    let engine = Engine::default();
+    let mut linker = Linker::<()>::new(&engine);
+
+    let result = unsafe {
+        bind(
+            &mut linker,
+            "native",
+            "get_size",
+            &common::library(),
+            b"get_size",
+            &[ParamType::Out(Box::new(ParamType::Pointer))],
+            None,
+        )
+    };
+    assert!(matches!(result, Err(Error::UnsupportedSignature { .. })));
+}
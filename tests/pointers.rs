@@ -0,0 +1,126 @@
+use wasmtime::Trap;
+use wasmtime_dl::ParamType;
+
+mod common;
+
+use common::Function;
+
+const MODULE: &str = r#"
+    (module
+        (import "native" "sum_bytes" (func $sum_bytes (param i32 i32) (result i32)))
+        (import "native" "read_i32" (func $read_i32 (param i32) (result i32)))
+        (import "native" "find_byte" (func $find_byte (param i32 i32 i32) (result i32)))
+        (import "native" "counter_get" (func $counter_get (param i32) (result i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 8) "\01\02\03\04")
+        (func (export "sum_bytes") (param i32 i32) (result i32)
+            (call $sum_bytes (local.get 0) (local.get 1)))
+        (func (export "read_i32") (param i32) (result i32)
+            (call $read_i32 (local.get 0)))
+        (func (export "find_byte") (param i32 i32 i32) (result i32)
+            (call $find_byte (local.get 0) (local.get 1) (local.get 2)))
+        (func (export "counter_get") (param i32) (result i32)
+            (call $counter_get (local.get 0))))
+"#;
+
+fn functions() -> Vec<Function<'static>> {
+    vec![
+        (
+            "sum_bytes",
+            vec![ParamType::Pointer, ParamType::I32],
+            Some(ParamType::I32),
+        ),
+        ("read_i32", vec![ParamType::Buffer(4)], Some(ParamType::I32)),
+        (
+            "find_byte",
+            vec![ParamType::Pointer, ParamType::I32, ParamType::I32],
+            Some(ParamType::Pointer),
+        ),
+        (
+            "counter_get",
+            vec![ParamType::I32],
+            Some(ParamType::Pointer),
+        ),
+    ]
+}
+
+#[test]
+fn translates_offsets_into_guest_memory() {
+    let (mut store, instance) = common::instantiate(MODULE, &functions());
+    let sum_bytes = instance
+        .get_typed_func::<(i32, i32), i32>(&mut store, "sum_bytes")
+        .unwrap();
+    let read_i32 = instance
+        .get_typed_func::<i32, i32>(&mut store, "read_i32")
+        .unwrap();
+
+    assert_eq!(sum_bytes.call(&mut store, (8, 4)).unwrap(), 10);
+    assert_eq!(read_i32.call(&mut store, 8).unwrap(), 0x04030201);
+    assert_eq!(read_i32.call(&mut store, 65532).unwrap(), 0);
+}
+
+#[test]
+fn traps_on_out_of_bounds_pointers() {
+    let (mut store, instance) = common::instantiate(MODULE, &functions());
+    let sum_bytes = instance
+        .get_typed_func::<(i32, i32), i32>(&mut store, "sum_bytes")
+        .unwrap();
+    let read_i32 = instance
+        .get_typed_func::<i32, i32>(&mut store, "read_i32")
+        .unwrap();
+
+    for (func, offset) in [(&read_i32, 65533), (&read_i32, -1)] {
+        let err = func.call(&mut store, offset).unwrap_err();
+        assert_eq!(err.downcast_ref(), Some(&Trap::MemoryOutOfBounds));
+    }
+    let err = sum_bytes.call(&mut store, (65537, 0)).unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Trap::MemoryOutOfBounds));
+}
+
+#[test]
+fn translates_returned_pointers_back_into_offsets() {
+    let (mut store, instance) = common::instantiate(MODULE, &functions());
+    let find_byte = instance
+        .get_typed_func::<(i32, i32, i32), i32>(&mut store, "find_byte")
+        .unwrap();
+
+    assert_eq!(find_byte.call(&mut store, (8, 4, 3)).unwrap(), 10);
+    assert_eq!(find_byte.call(&mut store, (8, 4, 5)).unwrap(), 0);
+}
+
+#[test]
+fn traps_on_returned_buffers_running_past_guest_memory() {
+    let module = r#"
+        (module
+            (import "native" "find_byte" (func $find_byte (param i32 i32 i32) (result i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 8) "\01\02\03\04")
+            (func (export "find_byte") (param i32 i32 i32) (result i32)
+                (call $find_byte (local.get 0) (local.get 1) (local.get 2))))
+    "#;
+    let find_pair = (
+        "find_byte",
+        vec![ParamType::Pointer, ParamType::I32, ParamType::I32],
+        Some(ParamType::Buffer(2)),
+    );
+    let (mut store, instance) = common::instantiate(module, &[find_pair]);
+    let find_pair = instance
+        .get_typed_func::<(i32, i32, i32), i32>(&mut store, "find_byte")
+        .unwrap();
+
+    assert_eq!(find_pair.call(&mut store, (8, 4, 3)).unwrap(), 10);
+    // The two bytes starting at the last byte of memory run past its end.
+    let err = find_pair.call(&mut store, (65535, 1, 0)).unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Trap::MemoryOutOfBounds));
+}
+
+#[test]
+fn traps_on_returned_pointers_outside_of_guest_memory() {
+    let (mut store, instance) = common::instantiate(MODULE, &functions());
+    let counter_get = instance
+        .get_typed_func::<i32, i32>(&mut store, "counter_get")
+        .unwrap();
+
+    let err = counter_get.call(&mut store, 0).unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Trap::MemoryOutOfBounds));
+}
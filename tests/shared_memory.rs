@@ -0,0 +1,153 @@
+use std::thread;
+
+use wasmtime::{Config, Engine, Linker, MemoryType, Module, SharedMemory, Store, Trap};
+use wasmtime_dl::{bind, bind_with_options, BindOptions, GuestMemory, ParamType};
+
+mod common;
+
+fn engine() -> Engine {
+    Engine::new(Config::new().wasm_threads(true)).unwrap()
+}
+
+#[test]
+fn reads_exported_shared_memories() {
+    let engine = engine();
+    let mut store = Store::new(&engine, ());
+    let mut linker = Linker::new(&engine);
+    unsafe {
+        bind(
+            &mut linker,
+            "native",
+            "sum_bytes",
+            &common::library(),
+            b"sum_bytes",
+            &[ParamType::Pointer, ParamType::I32],
+            Some(ParamType::I32),
+        )
+        .unwrap();
+    }
+
+    let module = Module::new(
+        &engine,
+        r#"
+        (module
+            (import "native" "sum_bytes" (func $sum_bytes (param i32 i32) (result i32)))
+            (memory (export "memory") 1 1 shared)
+            (data (i32.const 4) "\01\02\03")
+            (func (export "run") (result i32)
+                (call $sum_bytes (i32.const 4) (i32.const 3))))
+        "#,
+    )
+    .unwrap();
+    let instance = linker.instantiate(&mut store, &module).unwrap();
+    let run = instance
+        .get_typed_func::<(), i32>(&mut store, "run")
+        .unwrap();
+    assert_eq!(run.call(&mut store, ()).unwrap(), 6);
+}
+
+#[test]
+fn reads_strings_from_shared_memories() {
+    let engine = engine();
+    let linker = common::linker(
+        &engine,
+        &[
+            ("cstr_len", vec![ParamType::CStr], Some(ParamType::I32)),
+            ("str_len", vec![ParamType::Str], Some(ParamType::I32)),
+        ],
+        &BindOptions::default(),
+    );
+    let mut store = Store::new(&engine, ());
+    let module = Module::new(
+        &engine,
+        r#"
+        (module
+            (import "native" "cstr_len" (func $cstr_len (param i32) (result i32)))
+            (import "native" "str_len" (func $str_len (param i32 i32) (result i32)))
+            (memory (export "memory") 1 1 shared)
+            (data (i32.const 8) "hello\00")
+            (data (i32.const 65532) "tail")
+            (func (export "cstr_len") (param i32) (result i32)
+                (call $cstr_len (local.get 0)))
+            (func (export "str_len") (param i32 i32) (result i32)
+                (call $str_len (local.get 0) (local.get 1))))
+        "#,
+    )
+    .unwrap();
+    let instance = linker.instantiate(&mut store, &module).unwrap();
+    let cstr_len = instance
+        .get_typed_func::<i32, i32>(&mut store, "cstr_len")
+        .unwrap();
+    let str_len = instance
+        .get_typed_func::<(i32, i32), i32>(&mut store, "str_len")
+        .unwrap();
+
+    assert_eq!(cstr_len.call(&mut store, 8).unwrap(), 5);
+    let err = cstr_len.call(&mut store, 65532).unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Trap::MemoryOutOfBounds));
+    assert_eq!(str_len.call(&mut store, (65532, 4)).unwrap(), 4);
+    let err = str_len.call(&mut store, (65532, 5)).unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Trap::MemoryOutOfBounds));
+}
+
+#[test]
+fn writes_to_a_shared_memory_from_several_threads() {
+    let engine = engine();
+    let memory = SharedMemory::new(&engine, MemoryType::shared(1, 1)).unwrap();
+    let mut linker = Linker::new(&engine);
+    linker
+        .define(&Store::new(&engine, ()), "env", "memory", memory.clone())
+        .unwrap();
+    let out_i32 = ParamType::Out(Box::new(ParamType::I32));
+    unsafe {
+        bind_with_options(
+            &mut linker,
+            "native",
+            "get_size",
+            &common::library(),
+            b"get_size",
+            &[out_i32.clone(), out_i32],
+            None,
+            &BindOptions {
+                memory: GuestMemory::Shared(memory.clone()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    }
+    let module = Module::new(
+        &engine,
+        r#"
+        (module
+            (import "native" "get_size" (func $get_size (param i32 i32)))
+            (import "env" "memory" (memory 1 1 shared))
+            (func (export "get_size") (param i32)
+                (call $get_size (local.get 0) (i32.add (local.get 0) (i32.const 4)))))
+        "#,
+    )
+    .unwrap();
+
+    // Each thread has its own store and instance, and writes to its own part of the memory.
+    thread::scope(|scope| {
+        for i in 1..=4 {
+            let (linker, module) = (&linker, &module);
+            scope.spawn(move || {
+                let mut store = Store::new(module.engine(), ());
+                let instance = linker.instantiate(&mut store, module).unwrap();
+                let get_size = instance
+                    .get_typed_func::<i32, ()>(&mut store, "get_size")
+                    .unwrap();
+                get_size.call(&mut store, i * 8).unwrap();
+            });
+        }
+    });
+
+    let data: Vec<u8> = memory.data()[8..40]
+        .iter()
+        .map(|byte| unsafe { *byte.get() })
+        .collect();
+    let expected = [640i32.to_le_bytes(), 480i32.to_le_bytes()]
+        .concat()
+        .repeat(4);
+    assert_eq!(data, expected);
+}
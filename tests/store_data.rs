@@ -0,0 +1,53 @@
+use wasmtime::{Caller, Engine, Linker, Module, Store};
+use wasmtime_dl::{bind, ParamType};
+
+mod common;
+
+struct State {
+    logged: Vec<f32>,
+}
+
+#[test]
+fn binds_alongside_host_functions_using_store_data() {
+    let engine = Engine::default();
+    let mut store = Store::new(&engine, State { logged: Vec::new() });
+    let mut linker = Linker::<State>::new(&engine);
+    let library = common::library();
+    unsafe {
+        bind(
+            &mut linker,
+            "native",
+            "echo_f32",
+            &library,
+            b"echo_f32",
+            &[ParamType::F32],
+            Some(ParamType::F32),
+        )
+        .unwrap()
+    };
+    linker
+        .func_wrap("host", "log", |mut caller: Caller<'_, State>, x: f32| {
+            caller.data_mut().logged.push(x);
+        })
+        .unwrap();
+
+    let module = Module::new(
+        &engine,
+        r#"
+        (module
+            (import "native" "echo_f32" (func $echo_f32 (param f32) (result f32)))
+            (import "host" "log" (func $log (param f32)))
+            (func (export "run") (param f32)
+                (call $log (call $echo_f32 (local.get 0)))))
+        "#,
+    )
+    .unwrap();
+    let instance = linker.instantiate(&mut store, &module).unwrap();
+    let run = instance
+        .get_typed_func::<f32, ()>(&mut store, "run")
+        .unwrap();
+    run.call(&mut store, 2.5).unwrap();
+    run.call(&mut store, -4.0).unwrap();
+
+    assert_eq!(store.data().logged, [2.5, -4.0]);
+}
@@ -0,0 +1,179 @@
+use wasmtime::{Engine, Linker, Trap};
+use wasmtime_dl::{bind, BindOptions, Error, GuestAllocator, ParamType};
+
+mod common;
+
+use common::Function;
+
+const MODULE: &str = r#"
+    (module
+        (import "native" "cstr_len" (func $cstr_len (param i32) (result i32)))
+        (import "native" "str_len" (func $str_len (param i32 i32) (result i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 8) "hello\00")
+        (data (i32.const 65532) "tail")
+        (func (export "cstr_len") (param i32) (result i32)
+            (call $cstr_len (local.get 0)))
+        (func (export "str_len") (param i32 i32) (result i32)
+            (call $str_len (local.get 0) (local.get 1))))
+"#;
+
+fn functions() -> Vec<Function<'static>> {
+    vec![
+        ("cstr_len", vec![ParamType::CStr], Some(ParamType::I32)),
+        ("str_len", vec![ParamType::Str], Some(ParamType::I32)),
+    ]
+}
+
+#[test]
+fn passes_nul_terminated_strings() {
+    let (mut store, instance) = common::instantiate(MODULE, &functions());
+    let cstr_len = instance
+        .get_typed_func::<i32, i32>(&mut store, "cstr_len")
+        .unwrap();
+
+    assert_eq!(cstr_len.call(&mut store, 8).unwrap(), 5);
+    assert_eq!(cstr_len.call(&mut store, 10).unwrap(), 3);
+    for offset in [65532, 65536, -1] {
+        let err = cstr_len.call(&mut store, offset).unwrap_err();
+        assert_eq!(err.downcast_ref(), Some(&Trap::MemoryOutOfBounds));
+    }
+}
+
+#[test]
+fn copies_strings_with_a_length() {
+    let (mut store, instance) = common::instantiate(MODULE, &functions());
+    let str_len = instance
+        .get_typed_func::<(i32, i32), i32>(&mut store, "str_len")
+        .unwrap();
+
+    assert_eq!(str_len.call(&mut store, (8, 3)).unwrap(), 3);
+    assert_eq!(str_len.call(&mut store, (65532, 4)).unwrap(), 4);
+    assert_eq!(str_len.call(&mut store, (8, 0)).unwrap(), 0);
+    let err = str_len.call(&mut store, (65532, 5)).unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Trap::MemoryOutOfBounds));
+}
+
+#[test]
+fn rejects_string_returns() {
+    let engine = Engine::default();
+    let mut linker = Linker::<()>::new(&engine);
+
+    let result = unsafe {
+        bind(
+            &mut linker,
+            "native",
+            "str_len",
+            &common::library(),
+            b"str_len",
+            &[],
+            Some(ParamType::Str),
+        )
+    };
+    assert!(matches!(result, Err(Error::UnsupportedSignature { .. })));
+}
+
+const ALLOCATING_MODULE: &str = r#"
+    (module
+        (import "native" "greeting" (func $greeting (param i32) (result i32)))
+        (memory (export "memory") 1)
+        (global $next (mut i32) (i32.const 1024))
+        (func $alloc (param i32) (result i32)
+            (global.get $next)
+            (global.set $next (i32.add (global.get $next) (local.get 0))))
+        (func (export "malloc") (param i32) (result i32)
+            (call $alloc (local.get 0)))
+        (func (export "cabi_realloc") (param i32 i32 i32 i32) (result i32)
+            (call $alloc (local.get 3)))
+        (func (export "greeting") (param i32) (result i32)
+            (call $greeting (local.get 0))))
+"#;
+
+fn greeting(allocator: GuestAllocator) -> (Vec<u8>, i32) {
+    let linker = common::linker(
+        &Engine::default(),
+        &[("greeting", vec![ParamType::I32], Some(ParamType::CStr))],
+        &BindOptions {
+            allocator,
+            ..Default::default()
+        },
+    );
+    let (mut store, instance) = common::instantiate_with(&linker, ALLOCATING_MODULE);
+    let greeting = instance
+        .get_typed_func::<i32, i32>(&mut store, "greeting")
+        .unwrap();
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+
+    let offset = greeting.call(&mut store, 1).unwrap() as usize;
+    let copied = memory.data(&store)[offset..offset + 6].to_vec();
+    (copied, greeting.call(&mut store, 0).unwrap())
+}
+
+#[test]
+fn copies_returned_strings_into_guest_memory() {
+    for allocator in [
+        GuestAllocator::Malloc("malloc".to_owned()),
+        GuestAllocator::Realloc("cabi_realloc".to_owned()),
+    ] {
+        let (copied, null) = greeting(allocator);
+        assert_eq!(copied, b"hello\0");
+        assert_eq!(null, 0);
+    }
+}
+
+#[test]
+fn traps_when_the_allocator_fails() {
+    let linker = common::linker(
+        &Engine::default(),
+        &[("greeting", vec![ParamType::I32], Some(ParamType::CStr))],
+        &BindOptions::default(),
+    );
+    let (mut store, instance) = common::instantiate_with(
+        &linker,
+        r#"
+        (module
+            (import "native" "greeting" (func $greeting (param i32) (result i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 0) "guest data")
+            (func (export "malloc") (param i32) (result i32)
+                (i32.const 0))
+            (func (export "greeting") (result i32)
+                (call $greeting (i32.const 1))))
+        "#,
+    );
+    let greeting = instance
+        .get_typed_func::<(), i32>(&mut store, "greeting")
+        .unwrap();
+    let memory = instance.get_memory(&mut store, "memory").unwrap();
+
+    let err = greeting.call(&mut store, ()).unwrap_err();
+    assert!(format!("{err:?}").contains("failed to allocate"), "{err:?}");
+    assert_eq!(&memory.data(&store)[..10], b"guest data");
+}
+
+#[test]
+fn traps_on_buffers_outside_of_guest_memory() {
+    let linker = common::linker(
+        &Engine::default(),
+        &[("greeting", vec![ParamType::I32], Some(ParamType::CStr))],
+        &BindOptions::default(),
+    );
+    let (mut store, instance) = common::instantiate_with(
+        &linker,
+        r#"
+        (module
+            (import "native" "greeting" (func $greeting (param i32) (result i32)))
+            (memory (export "memory") 1)
+            (func (export "malloc") (param i32) (result i32)
+                (i32.const 65533))
+            (func (export "greeting") (result i32)
+                (call $greeting (i32.const 1))))
+        "#,
+    );
+    let greeting = instance
+        .get_typed_func::<(), i32>(&mut store, "greeting")
+        .unwrap();
+
+    let err = greeting.call(&mut store, ()).unwrap_err();
+    assert_eq!(err.downcast_ref(), Some(&Trap::MemoryOutOfBounds));
+}
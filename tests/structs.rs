@@ -0,0 +1,156 @@
+use wasmtime::{Engine, Linker, Trap};
+use wasmtime_dl::{bind, Error, Manifest, ParamType};
+
+mod common;
+
+use common::Function;
+
+const MODULE: &str = r#"
+    (module
+        (import "native" "point_scale" (func $point_scale (param i32 i32 f32)))
+        (import "native" "span_end" (func $span_end (param i32) (result i64)))
+        (import "native" "rect_make" (func $rect_make (param i32 f32 f32 f64 i32)))
+        (import "native" "rect_sum" (func $rect_sum (param i32) (result f64)))
+        (memory (export "memory") 1)
+        (func (export "point_scale") (param i32 i32 f32)
+            (call $point_scale (local.get 0) (local.get 1) (local.get 2)))
+        (func (export "span_end") (param i32) (result i64)
+            (call $span_end (local.get 0)))
+        (func (export "rect_make") (param i32 f32 f32 f64 i32)
+            (call $rect_make
+                (local.get 0) (local.get 1) (local.get 2) (local.get 3) (local.get 4)))
+        (func (export "rect_sum") (param i32) (result f64)
+            (call $rect_sum (local.get 0))))
+"#;
+
+fn point() -> ParamType {
+    ParamType::Struct(vec![ParamType::F32, ParamType::F32])
+}
+
+fn rect() -> ParamType {
+    ParamType::Struct(vec![point(), ParamType::F64, ParamType::I32])
+}
+
+fn functions() -> Vec<Function<'static>> {
+    let span = ParamType::Struct(vec![ParamType::I32, ParamType::I64]);
+    vec![
+        ("point_scale", vec![point(), ParamType::F32], Some(point())),
+        ("span_end", vec![span], Some(ParamType::I64)),
+        (
+            "rect_make",
+            vec![
+                ParamType::F32,
+                ParamType::F32,
+                ParamType::F64,
+                ParamType::I32,
+            ],
+            Some(rect()),
+        ),
+        ("rect_sum", vec![rect()], Some(ParamType::F64)),
+    ]
+}
+
+#[test]
+fn passes_and_returns_small_structs() {
+    let (mut store, instance) = common::instantiate(MODULE, &functions());
+    let memory = common::memory(&mut store, &instance);
+    let point_scale = instance
+        .get_typed_func::<(i32, i32, f32), ()>(&mut store, "point_scale")
+        .unwrap();
+    let span_end = instance
+        .get_typed_func::<i32, i64>(&mut store, "span_end")
+        .unwrap();
+
+    let point = [1.5f32.to_le_bytes(), (-2f32).to_le_bytes()].concat();
+    memory.write(&mut store, 0, &point).unwrap();
+    point_scale.call(&mut store, (16, 0, 2.0)).unwrap();
+    let scaled = [3f32.to_le_bytes(), (-4f32).to_le_bytes()].concat();
+    assert_eq!(memory.data(&store)[16..24], scaled);
+
+    // The `i64` field is aligned to 8 bytes, leaving padding after `start`.
+    memory.write(&mut store, 32, &5i32.to_le_bytes()).unwrap();
+    memory
+        .write(&mut store, 40, &(1i64 << 40).to_le_bytes())
+        .unwrap();
+    assert_eq!(span_end.call(&mut store, 32).unwrap(), 5 + (1 << 40));
+}
+
+#[test]
+fn passes_and_returns_nested_structs_in_memory() {
+    let (mut store, instance) = common::instantiate(MODULE, &functions());
+    let memory = common::memory(&mut store, &instance);
+    let rect_make = instance
+        .get_typed_func::<(i32, f32, f32, f64, i32), ()>(&mut store, "rect_make")
+        .unwrap();
+    let rect_sum = instance
+        .get_typed_func::<i32, f64>(&mut store, "rect_sum")
+        .unwrap();
+
+    rect_make.call(&mut store, (64, 0.5, 1.0, 2.25, 7)).unwrap();
+    let rect = [
+        &0.5f32.to_le_bytes()[..],
+        &1f32.to_le_bytes(),
+        &2.25f64.to_le_bytes(),
+        &7i32.to_le_bytes(),
+        &[0; 4],
+    ]
+    .concat();
+    assert_eq!(memory.data(&store)[64..88], rect);
+    assert_eq!(rect_sum.call(&mut store, 64).unwrap(), 10.75);
+}
+
+#[test]
+fn traps_on_out_of_bounds_structs() {
+    let (mut store, instance) = common::instantiate(MODULE, &functions());
+    let point_scale = instance
+        .get_typed_func::<(i32, i32, f32), ()>(&mut store, "point_scale")
+        .unwrap();
+
+    for (ret, arg) in [(65532, 0), (0, 65532)] {
+        let err = point_scale.call(&mut store, (ret, arg, 1.0)).unwrap_err();
+        assert_eq!(err.downcast_ref(), Some(&Trap::MemoryOutOfBounds));
+    }
+}
+
+#[test]
+fn rejects_structs_with_unsupported_fields() {
+    let engine = Engine::default();
+    let mut linker = Linker::<()>::new(&engine);
+    let library = common::library();
+
+    for ty in [
+        ParamType::Struct(vec![]),
+        ParamType::Struct(vec![ParamType::F32, ParamType::Pointer]),
+    ] {
+        let result = unsafe {
+            bind(
+                &mut linker,
+                "native",
+                "span_end",
+                &library,
+                b"span_end",
+                &[ty],
+                Some(ParamType::I64),
+            )
+        };
+        assert!(matches!(result, Err(Error::UnsupportedSignature { .. })));
+    }
+}
+
+#[test]
+fn reads_structs_from_manifests() {
+    let manifest = Manifest::from_toml(
+        r#"
+        library = "libnative.so"
+        module = "native"
+
+        [[functions]]
+        name = "point_scale"
+        params = [{ struct = ["f32", "f32"] }, "f32"]
+        returns = { struct = ["f32", "f32"] }
+        "#,
+    )
+    .unwrap();
+    assert_eq!(manifest.functions[0].params, [point(), ParamType::F32]);
+    assert_eq!(manifest.functions[0].returns, Some(point()));
+}
@@ -0,0 +1,72 @@
+use wasmtime::{Engine, Module};
+use wasmtime_dl::{validate, Binding, Error, Manifest, ParamType};
+
+const MODULE: &str = r#"
+    (module
+        (import "native" "echo_f32" (func (param f32) (result f32)))
+        (import "native" "sum_bytes" (func (param i32 i32) (result i32)))
+        (import "native" "read_i32" (func (param i32) (result i32)))
+        (import "host" "log" (func (param i32))))
+"#;
+
+fn binding(name: &str, params: &[ParamType], returns: Option<ParamType>) -> Binding {
+    Binding {
+        module: "native".to_owned(),
+        name: name.to_owned(),
+        params: params.to_vec(),
+        returns,
+        memory64: false,
+    }
+}
+
+#[test]
+fn lists_every_mismatched_binding() {
+    let engine = Engine::default();
+    let module = Module::new(&engine, MODULE).unwrap();
+    let bindings = [
+        binding("echo_f32", &[ParamType::F64], Some(ParamType::F64)),
+        binding(
+            "sum_bytes",
+            &[ParamType::Pointer, ParamType::I32],
+            Some(ParamType::I32),
+        ),
+        binding("read_i32", &[ParamType::Buffer(4)], None),
+        binding("not_imported", &[], None),
+    ];
+
+    let Err(Error::ImportMismatch(mismatches)) = validate(&module, &bindings) else {
+        panic!("expected mismatches");
+    };
+    let names: Vec<_> = mismatches.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, ["echo_f32", "read_i32"]);
+    assert_eq!(
+        mismatches[1].to_string(),
+        "native.read_i32 is imported as (type (func (param i32) (result i32))) \
+         but bound as (type (func (param i32)))"
+    );
+}
+
+#[test]
+fn accepts_matching_manifests() {
+    let engine = Engine::default();
+    let module = Module::new(&engine, MODULE).unwrap();
+    let manifest = Manifest::from_toml(
+        r#"
+        library = "libnative.so"
+        module = "native"
+
+        [[functions]]
+        name = "echo_f32"
+        params = ["f32"]
+        returns = "f32"
+
+        [[functions]]
+        name = "read_i32"
+        params = ["pointer"]
+        returns = "i32"
+        "#,
+    )
+    .unwrap();
+
+    validate(&module, &manifest.bindings()).unwrap();
+}
@@ -0,0 +1,105 @@
+use wasmtime::{Engine, Linker, Module, Store};
+use wasmtime_dl::{bind_with_options, BindOptions, Error, Manifest, ParamType};
+
+mod common;
+
+const MODULE: &str = r#"
+    (module
+        (import "native" "sum_variadic" (func $sum (param i32 i32 f32 i64 f64) (result f64)))
+        (memory (export "memory") 1)
+        (data (i32.const 8) "ifld\00")
+        (func (export "sum") (param i32 i32 f32 i64 f64) (result f64)
+            (call $sum (local.get 0) (local.get 1) (local.get 2) (local.get 3) (local.get 4))))
+"#;
+
+fn params() -> Vec<ParamType> {
+    vec![
+        ParamType::CStr,
+        ParamType::I32,
+        ParamType::F32,
+        ParamType::I64,
+        ParamType::F64,
+    ]
+}
+
+fn sum(linker: &Linker<()>) -> f64 {
+    let mut store = Store::new(linker.engine(), ());
+    let module = Module::new(linker.engine(), MODULE).unwrap();
+    let instance = linker.instantiate(&mut store, &module).unwrap();
+    let sum = instance
+        .get_typed_func::<(i32, i32, f32, i64, f64), f64>(&mut store, "sum")
+        .unwrap();
+    sum.call(&mut store, (8, 1, 2.5, 3, 4.25)).unwrap()
+}
+
+#[test]
+fn promotes_floats_passed_as_variadic_arguments() {
+    let engine = Engine::default();
+    let mut linker = Linker::new(&engine);
+    let options = BindOptions {
+        fixed_params: Some(1),
+        ..BindOptions::default()
+    };
+    unsafe {
+        bind_with_options(
+            &mut linker,
+            "native",
+            "sum_variadic",
+            &common::library(),
+            b"sum_variadic",
+            &params(),
+            Some(ParamType::F64),
+            &options,
+        )
+        .unwrap();
+    }
+
+    assert_eq!(sum(&linker), 10.75);
+}
+
+#[test]
+fn loads_variadic_functions_from_manifests() {
+    let manifest = Manifest::from_toml(&format!(
+        r#"
+        library = {:?}
+        module = "native"
+
+        [[functions]]
+        name = "sum_variadic"
+        params = ["cstr", "i32", "f32", "i64", "f64"]
+        returns = "f64"
+        fixed_params = 1
+        "#,
+        common::library_path()
+    ))
+    .unwrap();
+    let engine = Engine::default();
+    let mut linker = Linker::new(&engine);
+    unsafe { manifest.apply(&mut linker).unwrap() };
+
+    assert_eq!(sum(&linker), 10.75);
+}
+
+#[test]
+fn rejects_more_fixed_parameters_than_parameters() {
+    let engine = Engine::default();
+    let mut linker = Linker::<()>::new(&engine);
+    let options = BindOptions {
+        fixed_params: Some(6),
+        ..BindOptions::default()
+    };
+
+    let result = unsafe {
+        bind_with_options(
+            &mut linker,
+            "native",
+            "sum_variadic",
+            &common::library(),
+            b"sum_variadic",
+            &params(),
+            Some(ParamType::F64),
+            &options,
+        )
+    };
+    assert!(matches!(result, Err(Error::UnsupportedSignature { .. })));
+}